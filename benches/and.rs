@@ -0,0 +1,300 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use lieutenant::argument::{Nbt, SnbtParser, U32Parser};
+use lieutenant::command::builder::{literal, space, CommandBuilder};
+use lieutenant::parser::{IterParser, Literal, OneOrMoreSpace, Opt};
+
+// The design And used before it replayed its left parser: the extract of A is kept in the state, and
+// cloned for every further match of B. It nests the extracts instead of flattening them, which costs
+// the same to clone. Kept here as the baseline the replaying And is measured against.
+struct CloningAnd<A, B> {
+    a: A,
+    b: B,
+}
+
+enum CloningState<A: IterParser, B: IterParser> {
+    A(A::ParserState),
+    B {
+        a_next: Option<A::ParserState>,
+        ext: A::Extract,
+        consumed: usize,
+        b_state: B::ParserState,
+    },
+}
+
+impl<A: IterParser, B: IterParser> Default for CloningState<A, B> {
+    fn default() -> Self {
+        Self::A(A::ParserState::default())
+    }
+}
+
+impl<A, B> Clone for CloningState<A, B>
+where
+    A: IterParser,
+    A::Extract: Clone,
+    B: IterParser,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::A(a_state) => Self::A(a_state.clone()),
+            Self::B {
+                a_next,
+                ext,
+                consumed,
+                b_state,
+            } => Self::B {
+                a_next: a_next.clone(),
+                ext: ext.clone(),
+                consumed: *consumed,
+                b_state: b_state.clone(),
+            },
+        }
+    }
+}
+
+impl<A, B> IterParser for CloningAnd<A, B>
+where
+    A: IterParser,
+    A::Extract: Clone,
+    B: IterParser,
+{
+    type Extract = (A::Extract, B::Extract);
+    type ParserState = CloningState<A, B>;
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        state: Self::ParserState,
+        input: &'p str,
+    ) -> (
+        anyhow::Result<(Self::Extract, &'p str)>,
+        Option<Self::ParserState>,
+    ) {
+        let (a_next, ext, consumed, b_state) = match state {
+            CloningState::A(a_state) => match self.a.parse(a_state, input) {
+                (Ok((ext, a_out)), a_next) => (
+                    a_next,
+                    ext,
+                    input.len() - a_out.len(),
+                    B::ParserState::default(),
+                ),
+                (Err(err), a_next) => return (Err(err), a_next.map(CloningState::A)),
+            },
+            CloningState::B {
+                a_next,
+                ext,
+                consumed,
+                b_state,
+            } => (a_next, ext, consumed, b_state),
+        };
+
+        let (b_res, b_next) = self.b.parse(b_state, &input[consumed..]);
+        match (b_res, b_next) {
+            // B has more to give, so the extract of A is cloned to keep it for the next match of B.
+            (Ok((b_ext, b_out)), Some(b_state)) => (
+                Ok(((ext.clone(), b_ext), b_out)),
+                Some(CloningState::B {
+                    a_next,
+                    ext,
+                    consumed,
+                    b_state,
+                }),
+            ),
+            (Ok((b_ext, b_out)), None) => (Ok(((ext, b_ext), b_out)), a_next.map(CloningState::A)),
+            (Err(err), Some(b_state)) => (
+                Err(err),
+                Some(CloningState::B {
+                    a_next,
+                    ext,
+                    consumed,
+                    b_state,
+                }),
+            ),
+            (Err(err), None) => (Err(err), a_next.map(CloningState::A)),
+        }
+    }
+
+    fn regex(&self) -> String {
+        format!("({})({})", self.a.regex(), self.b.regex())
+    }
+}
+
+fn cloning<A, B>(a: A, b: B) -> CloningAnd<A, B> {
+    CloningAnd { a, b }
+}
+
+fn cloning_argument() -> CloningAnd<OneOrMoreSpace, U32Parser> {
+    cloning(OneOrMoreSpace::new(), U32Parser::default())
+}
+
+// Appends a space and an argument like `.space().arg::<u32>()`, so both chains have the same shape.
+fn cloning_space_argument<A: IterParser>(
+    chain: A,
+) -> CloningAnd<CloningAnd<A, OneOrMoreSpace>, U32Parser> {
+    cloning(cloning(chain, OneOrMoreSpace::new()), U32Parser::default())
+}
+
+// What a command does with its parser: take the first match that consumes the whole input.
+fn full_match<P: IterParser>(parser: &P, input: &str) -> Option<P::Extract> {
+    let mut state = Some(P::ParserState::default());
+    while let Some(current) = state {
+        let (result, next) = parser.parse(current, input);
+        if let Ok((ext, "")) = result {
+            return Some(ext);
+        }
+        state = next;
+    }
+    None
+}
+
+// Sixteen required arguments. Every argument matches once, so this measures the plain cost of a deep chain.
+fn required_arguments(c: &mut Criterion) {
+    let replaying = literal("/fill")
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>();
+
+    let cloning_chain = {
+        let chain = cloning_space_argument(Literal::new(String::from("/fill")));
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        let chain = cloning_space_argument(chain);
+        cloning_space_argument(chain)
+    };
+
+    let input = "/fill 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16";
+    let mut group = c.benchmark_group("16 required arguments");
+    group.bench_function("replay", |b| {
+        b.iter(|| full_match(&replaying, black_box(input)).unwrap())
+    });
+    group.bench_function("clone (baseline)", |b| {
+        b.iter(|| full_match(&cloning_chain, black_box(input)).unwrap())
+    });
+    group.finish();
+}
+
+// Eight optional arguments followed by four required ones. The optional arguments greedily consume
+// numbers meant for the required ones, so And has to backtrack and hand out the left extract many times.
+fn backtracking_arguments(c: &mut Criterion) {
+    let replaying = literal("/spread")
+        .followed_by(Opt::new(space().arg::<u32>()))
+        .followed_by(Opt::new(space().arg::<u32>()))
+        .followed_by(Opt::new(space().arg::<u32>()))
+        .followed_by(Opt::new(space().arg::<u32>()))
+        .followed_by(Opt::new(space().arg::<u32>()))
+        .followed_by(Opt::new(space().arg::<u32>()))
+        .followed_by(Opt::new(space().arg::<u32>()))
+        .followed_by(Opt::new(space().arg::<u32>()))
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>()
+        .space()
+        .arg::<u32>();
+
+    let chain = Literal::new(String::from("/spread"));
+    let chain = cloning(chain, Opt::new(cloning_argument()));
+    let chain = cloning(chain, Opt::new(cloning_argument()));
+    let chain = cloning(chain, Opt::new(cloning_argument()));
+    let chain = cloning(chain, Opt::new(cloning_argument()));
+    let chain = cloning(chain, Opt::new(cloning_argument()));
+    let chain = cloning(chain, Opt::new(cloning_argument()));
+    let chain = cloning(chain, Opt::new(cloning_argument()));
+    let chain = cloning(chain, Opt::new(cloning_argument()));
+    let chain = cloning_space_argument(chain);
+    let chain = cloning_space_argument(chain);
+    let chain = cloning_space_argument(chain);
+    let cloning_chain = cloning_space_argument(chain);
+
+    let input = "/spread 1 2 3 4 5 6 7 8";
+    let mut group = c.benchmark_group("8 optional and 4 required arguments");
+    group.bench_function("replay", |b| {
+        b.iter(|| full_match(&replaying, black_box(input)).unwrap())
+    });
+    group.bench_function("clone (baseline)", |b| {
+        b.iter(|| full_match(&cloning_chain, black_box(input)).unwrap())
+    });
+    group.finish();
+}
+
+// Like the arguments above, but every argument is a compound that is costly to clone.
+fn backtracking_compounds(c: &mut Criterion) {
+    let replaying = literal("/merge")
+        .followed_by(Opt::new(space().arg::<Nbt>()))
+        .followed_by(Opt::new(space().arg::<Nbt>()))
+        .followed_by(Opt::new(space().arg::<Nbt>()))
+        .followed_by(Opt::new(space().arg::<Nbt>()))
+        .space()
+        .arg::<Nbt>()
+        .space()
+        .arg::<Nbt>();
+
+    let compound = || cloning(OneOrMoreSpace::new(), SnbtParser);
+    let chain = Literal::new(String::from("/merge"));
+    let chain = cloning(chain, Opt::new(compound()));
+    let chain = cloning(chain, Opt::new(compound()));
+    let chain = cloning(chain, Opt::new(compound()));
+    let chain = cloning(chain, Opt::new(compound()));
+    let chain = cloning(cloning(chain, OneOrMoreSpace::new()), SnbtParser);
+    let cloning_chain = cloning(cloning(chain, OneOrMoreSpace::new()), SnbtParser);
+
+    let chest = r#"{Items:[{id:"minecraft:stone",Count:64b},{id:"minecraft:dirt",Count:32b}],Name:"chest"}"#;
+    let input = format!("/merge {}", [chest; 4].join(" "));
+    let mut group = c.benchmark_group("4 optional and 2 required compound arguments");
+    group.bench_function("replay", |b| {
+        b.iter(|| full_match(&replaying, black_box(&input)).unwrap())
+    });
+    group.bench_function("clone (baseline)", |b| {
+        b.iter(|| full_match(&cloning_chain, black_box(&input)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    required_arguments,
+    backtracking_arguments,
+    backtracking_compounds
+);
+criterion_main!(benches);
@@ -0,0 +1,210 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr};
+
+/// Derives `Keyword` and `Argument` for an enum of unit variants. Each variant is matched by a keyword,
+/// which is the snake cased name of the variant unless it is set with `#[keyword("...")]`.
+#[proc_macro_derive(Argument, attributes(keyword))]
+pub fn derive_argument(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    argument(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derives `FromCommand` for a struct. The struct needs a `#[command("...")]` attribute with the literals
+/// the command starts with, and every field is parsed as an `#[arg]` in declaration order.
+#[proc_macro_derive(Command, attributes(command, arg))]
+pub fn derive_command(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    command(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn argument(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Argument can only be derived for enums",
+            ))
+        }
+    };
+
+    let mut keywords = Vec::new();
+    let mut variants = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "Argument can only be derived for enums with unit variants",
+            ));
+        }
+
+        let mut keyword = snake_case(&variant.ident.to_string());
+        for attr in &variant.attrs {
+            if attr.path().is_ident("keyword") {
+                keyword = attr.parse_args::<LitStr>()?.value();
+            }
+        }
+        keywords.push(keyword);
+        variants.push(&variant.ident);
+    }
+
+    let name = &input.ident;
+    let indices = 0..variants.len();
+
+    Ok(quote! {
+        impl ::lieutenant::argument::Keyword for #name {
+            fn keywords() -> &'static [&'static str] {
+                &[#(#keywords),*]
+            }
+
+            fn from_index(index: usize) -> ::std::option::Option<Self> {
+                match index {
+                    #(#indices => ::std::option::Option::Some(Self::#variants),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+        }
+
+        impl ::lieutenant::argument::Argument for #name {
+            type Parser = ::lieutenant::argument::KeywordParser<Self>;
+            type ParserState = ();
+        }
+    })
+}
+
+fn command(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Command can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut literals = None;
+    for attr in &input.attrs {
+        if attr.path().is_ident("command") {
+            literals = Some(attr.parse_args::<LitStr>()?);
+        }
+    }
+    let literals = match literals {
+        Some(literals) => literals,
+        None => {
+            return Err(Error::new(
+                Span::call_site(),
+                "Command needs a #[command(\"...\")] attribute",
+            ))
+        }
+    };
+    let words = literals.value();
+    let mut words = words
+        .split_whitespace()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    if words.is_empty() {
+        return Err(Error::new_spanned(literals, "The command can not be empty"));
+    }
+    let first = words.remove(0);
+
+    let mut idents = Vec::new();
+    let mut types = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        if !field.attrs.iter().any(|attr| attr.path().is_ident("arg")) {
+            return Err(Error::new_spanned(
+                field,
+                "Every field needs an #[arg] attribute",
+            ));
+        }
+        idents.push(
+            field
+                .ident
+                .clone()
+                .unwrap_or_else(|| format_ident!("arg{}", index)),
+        );
+        types.push(&field.ty);
+    }
+
+    let mut parser_type = quote!(::lieutenant::parser::Literal);
+    let mut parser = quote!(::lieutenant::command::builder::literal(#first));
+    for word in &words {
+        parser_type = quote! {
+            ::lieutenant::parser::And<
+                ::lieutenant::parser::And<#parser_type, ::lieutenant::parser::OneOrMoreSpace>,
+                ::lieutenant::parser::Literal,
+            >
+        };
+        parser =
+            quote!(#parser.space().followed_by(::lieutenant::command::builder::literal(#word)));
+    }
+    // The fields are collected into nested pairs `((((), a), b), c)` one argument at a time, so the
+    // number of fields is not limited by the arity of the handler tuples.
+    parser_type = quote!(::lieutenant::parser::Map<#parser_type, fn()>);
+    parser = quote!(::lieutenant::parser::Map::new(#parser, (|| ()) as fn()));
+    let mut fields_type = quote!(());
+    let mut fields_pattern = quote!(());
+    for (ident, ty) in idents.iter().zip(&types) {
+        let pair_type = quote!((#fields_type, #ty));
+        parser_type = quote! {
+            ::lieutenant::parser::Map<
+                ::lieutenant::parser::And<
+                    ::lieutenant::parser::And<#parser_type, ::lieutenant::parser::OneOrMoreSpace>,
+                    <#ty as ::lieutenant::argument::Argument>::Parser,
+                >,
+                fn(#fields_type, #ty) -> #pair_type,
+            >
+        };
+        parser = quote! {
+            ::lieutenant::parser::Map::new(
+                #parser.space().arg::<#ty>(),
+                (|fields, arg| (fields, arg)) as fn(#fields_type, #ty) -> #pair_type,
+            )
+        };
+        fields_type = pair_type;
+        fields_pattern = quote!((#fields_pattern, #ident));
+    }
+
+    let construct = match fields {
+        Fields::Named(_) => quote!(Self { #(#idents),* }),
+        Fields::Unnamed(_) => quote!(Self(#(#idents),*)),
+        Fields::Unit => quote!(Self),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::lieutenant::command::FromCommand for #name #ty_generics #where_clause {
+            type Parser = ::lieutenant::parser::Map<#parser_type, fn(#fields_type) -> Self>;
+
+            fn parser() -> Self::Parser {
+                use ::lieutenant::command::builder::CommandBuilder;
+                let construct: fn(#fields_type) -> Self = |#fields_pattern| #construct;
+                ::lieutenant::parser::Map::new(#parser, construct)
+            }
+        }
+    })
+}
+
+fn snake_case(name: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
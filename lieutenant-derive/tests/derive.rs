@@ -0,0 +1,115 @@
+// Wide has more fields than a handler can take arguments, and its parser nests deeper than the default limit.
+#![recursion_limit = "512"]
+
+use lieutenant::argument::Keyword;
+use lieutenant::command::builder::CommandBuilder;
+use lieutenant::command::{Command, FromCommand};
+use lieutenant::parser::IterParser;
+use lieutenant::{Argument, Command};
+
+#[derive(Argument, Debug, PartialEq)]
+enum GameMode {
+    Survival,
+    Creative,
+    #[keyword("spectate")]
+    Spectator,
+}
+
+#[derive(Argument, Debug, PartialEq)]
+enum Weather {
+    Clear,
+    Rain,
+    ThunderStorm,
+}
+
+#[derive(Command, Debug, PartialEq)]
+#[command("/gamemode")]
+struct SetGameMode {
+    #[arg]
+    mode: GameMode,
+}
+
+#[derive(Command, Debug, PartialEq)]
+#[command("/weather set")]
+struct SetWeather {
+    #[arg]
+    weather: Weather,
+    #[arg]
+    duration: u32,
+}
+
+macro_rules! wide {
+    ($($field:ident),*) => {
+        #[derive(Command, Debug, PartialEq)]
+        #[command("/wide")]
+        struct Wide {
+            $(#[arg] $field: u32,)*
+        }
+
+        impl Wide {
+            fn sum(&self) -> u32 {
+                0 $(+ self.$field)*
+            }
+        }
+    };
+}
+
+wide!(
+    f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11, f12, f13, f14, f15, f16, f17, f18, f19, f20,
+    f21, f22, f23, f24, f25, f26, f27, f28, f29, f30, f31, f32, f33, f34, f35, f36, f37, f38, f39,
+    f40, f41, f42, f43, f44, f45, f46, f47, f48, f49, f50, f51, f52, f53, f54, f55, f56, f57, f58,
+    f59, f60, f61, f62, f63, f64, f65, f66, f67, f68, f69
+);
+
+#[test]
+fn argument() {
+    let parser = <GameMode as lieutenant::argument::Argument>::Parser::default();
+
+    let (res, _) = parser.parse((), "creative");
+    assert_eq!(res.unwrap().0, (GameMode::Creative,));
+    let (res, _) = parser.parse((), "SPECTATE me");
+    assert_eq!(res.unwrap(), ((GameMode::Spectator,), " me"));
+    assert!(parser.parse((), "spectator").0.is_err());
+    assert_eq!(
+        parser.regex(),
+        "((?i:survival)|(?i:creative)|(?i:spectate))"
+    );
+    assert_eq!(parser.suggest("s").len(), 2);
+    assert_eq!(GameMode::from_index(2), Some(GameMode::Spectator));
+    assert_eq!(GameMode::from_index(3), None);
+
+    let parser = <Weather as lieutenant::argument::Argument>::Parser::default();
+    let (res, _) = parser.parse((), "thunder_storm");
+    assert_eq!(res.unwrap().0, (Weather::ThunderStorm,));
+}
+
+#[test]
+fn command() {
+    let cmd =
+        SetGameMode::parser().on_call(|cmd: SetGameMode| move || cmd.mode == GameMode::Survival);
+    assert!(cmd.call((), "/gamemode survival").unwrap());
+    assert!(!cmd.call((), "/gamemode creative").unwrap());
+    assert!(cmd.call((), "/gamemode").is_err());
+
+    let cmd = SetWeather::parser().on_call(|cmd: SetWeather| move || cmd.duration);
+    assert_eq!(cmd.call((), "/weather set rain 600").unwrap(), 600);
+    assert!(cmd.call((), "/weather rain 600").is_err());
+    assert_eq!(
+        cmd.regex(),
+        format!("({})(\\s*)", SetWeather::parser().regex())
+    );
+}
+
+#[test]
+fn many_fields() {
+    let cmd = Wide::parser().on_call(|cmd: Wide| move || cmd.sum());
+    let input = format!(
+        "/wide {}",
+        (1..=70)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    assert_eq!(cmd.call((), &input).unwrap(), 2485);
+    assert!(cmd.call((), "/wide 1 2 3").is_err());
+}
@@ -0,0 +1,401 @@
+use anyhow::{anyhow, bail, Result};
+use indexmap::IndexMap;
+
+use crate::brigadier::{ArgumentType, NodeKind};
+use crate::feedback::Message;
+use crate::parser::IterParser;
+
+use super::{
+    nbt::snbt_compound, resource::resource_location, Argument, Nbt, Registries, Registry,
+    ResourceLocation,
+};
+
+/// The blocks of the game with their properties, used to validate block states.
+pub trait BlockRegistry: Registry {
+    /// The values `property` can have on `block`, or `None` if the block has no such property.
+    fn property_values(&self, block: &ResourceLocation, property: &str) -> Option<Vec<String>>;
+
+    fn contains_tag(&self, tag: &ResourceLocation) -> bool;
+}
+
+/// A block with some of its properties and block entity data, like `minecraft:oak_stairs[facing=north]{}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockState {
+    pub block: ResourceLocation,
+    pub properties: IndexMap<String, String>,
+    pub nbt: Option<Nbt>,
+}
+
+/// Matches blocks either by id like a [`BlockState`], or by a tag like `#minecraft:logs[axis=y]`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockPredicate {
+    Block(BlockState),
+    Tag {
+        tag: ResourceLocation,
+        properties: IndexMap<String, String>,
+        nbt: Option<Nbt>,
+    },
+}
+
+fn property_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '+'
+}
+
+fn word(input: &str) -> (String, &str) {
+    let end = input.find(|c| !property_char(c)).unwrap_or(input.len());
+    (String::from(&input[..end]), &input[end..])
+}
+
+/// Reads an optional `[name=value,...]` property list of `block`, which may also be a tag.
+fn properties<'a>(
+    block: &ResourceLocation,
+    input: &'a str,
+) -> Result<(IndexMap<String, String>, &'a str)> {
+    let unclosed = || {
+        anyhow!(Message::translatable(
+            "argument.block.property.unclosed",
+            vec![]
+        ))
+    };
+    let mut properties = IndexMap::new();
+    let mut out = match input.strip_prefix('[') {
+        Some(out) => out.trim_start(),
+        None => return Ok((properties, input)),
+    };
+    if let Some(rest) = out.strip_prefix(']') {
+        return Ok((properties, rest));
+    }
+
+    loop {
+        let (name, rest) = word(out);
+        if name.is_empty() {
+            return Err(unclosed());
+        }
+        let novalue = || {
+            anyhow!(Message::translatable(
+                "argument.block.property.novalue",
+                vec![Message::text(&name), Message::text(block.to_string())]
+            ))
+        };
+        let rest = rest.trim_start().strip_prefix('=').ok_or_else(novalue)?;
+        let (value, rest) = word(rest.trim_start());
+        if value.is_empty() {
+            return Err(novalue());
+        }
+        if properties.contains_key(&name) {
+            bail!(Message::translatable(
+                "argument.block.property.duplicate",
+                vec![Message::text(name), Message::text(block.to_string())]
+            ));
+        }
+        properties.insert(name, value);
+
+        let rest = rest.trim_start();
+        if let Some(rest) = rest.strip_prefix(',') {
+            out = rest.trim_start();
+        } else if let Some(rest) = rest.strip_prefix(']') {
+            return Ok((properties, rest));
+        } else {
+            return Err(unclosed());
+        }
+    }
+}
+
+/// Reads optional trailing block entity or item data.
+pub(crate) fn trailing_nbt(input: &str) -> Result<(Option<Nbt>, &str)> {
+    if input.starts_with('{') {
+        snbt_compound(input).map(|(nbt, out)| (Some(nbt), out))
+    } else {
+        Ok((None, input))
+    }
+}
+
+fn validate(
+    registry: &dyn BlockRegistry,
+    block: &ResourceLocation,
+    properties: &IndexMap<String, String>,
+) -> Result<()> {
+    if !registry.contains(block) {
+        bail!(Message::translatable(
+            "argument.block.id.invalid",
+            vec![Message::text(block.to_string())]
+        ));
+    }
+    for (name, value) in properties {
+        let values = registry.property_values(block, name).ok_or_else(|| {
+            anyhow!(Message::translatable(
+                "argument.block.property.unknown",
+                vec![Message::text(block.to_string()), Message::text(name)]
+            ))
+        })?;
+        if !values.contains(value) {
+            bail!(Message::translatable(
+                "argument.block.property.invalid",
+                vec![
+                    Message::text(block.to_string()),
+                    Message::text(value),
+                    Message::text(name)
+                ]
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn block_state<'p>(
+    registry: Option<&dyn BlockRegistry>,
+    input: &'p str,
+) -> Result<(BlockState, &'p str)> {
+    let (block, out) = resource_location(input)?;
+    let (properties, out) = properties(&block, out)?;
+    let (nbt, out) = trailing_nbt(out)?;
+    if let Some(registry) = registry {
+        validate(registry, &block, &properties)?;
+    }
+    Ok((
+        BlockState {
+            block,
+            properties,
+            nbt,
+        },
+        out,
+    ))
+}
+
+const BLOCK_REGEX: &str = "([a-z0-9_\\.\\-]*:)?[a-z0-9_\\./\\-]+(\\[[^\\]]*\\])?(\\{.*\\})?";
+
+/// Parses a [`BlockState`]. Unless the [`Registries`] have blocks, any well formed block and properties
+/// are accepted.
+#[derive(Default)]
+pub struct BlockStateParser;
+
+impl IterParser for BlockStateParser {
+    type Extract = (BlockState,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        self.parse_in(&(), state, input)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn parse_in<'p>(
+        &self,
+        registries: &dyn Registries,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        let result = block_state(registries.blocks(), input);
+        (result.map(|(state, out)| ((state,), out)), None)
+    }
+
+    fn regex(&self) -> String {
+        String::from(BLOCK_REGEX)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument("block", ArgumentType::BlockState)]]
+    }
+}
+
+impl Argument for BlockState {
+    type Parser = BlockStateParser;
+    type ParserState = ();
+}
+
+/// Parses a [`BlockPredicate`]. Properties of tags are only checked to be well formed, since the blocks
+/// in a tag can have different properties.
+#[derive(Default)]
+pub struct BlockPredicateParser;
+
+impl IterParser for BlockPredicateParser {
+    type Extract = (BlockPredicate,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        self.parse_in(&(), state, input)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn parse_in<'p>(
+        &self,
+        registries: &dyn Registries,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        let parse = || {
+            let input = match input.strip_prefix('#') {
+                Some(input) => input,
+                None => {
+                    let (state, out) = block_state(registries.blocks(), input)?;
+                    return Ok(((BlockPredicate::Block(state),), out));
+                }
+            };
+
+            let (tag, out) = resource_location(input)?;
+            let (properties, out) = properties(&tag, out)?;
+            let (nbt, out) = trailing_nbt(out)?;
+            if let Some(registry) = registries.blocks() {
+                if !registry.contains_tag(&tag) {
+                    bail!(Message::translatable(
+                        "arguments.block.tag.unknown",
+                        vec![Message::text(tag.to_string())]
+                    ));
+                }
+            }
+            Ok((
+                (BlockPredicate::Tag {
+                    tag,
+                    properties,
+                    nbt,
+                },),
+                out,
+            ))
+        };
+        (parse(), None)
+    }
+
+    fn regex(&self) -> String {
+        format!("#?{}", BLOCK_REGEX)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument(
+            "predicate",
+            ArgumentType::BlockPredicate,
+        )]]
+    }
+}
+
+impl Argument for BlockPredicate {
+    type Parser = BlockPredicateParser;
+    type ParserState = ();
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::argument::{Nbt, Registries, Registry, ResourceLocation};
+    use crate::parser::IterParser;
+    use crate::regex::{DFA, NFA};
+
+    use super::{BlockPredicate, BlockPredicateParser, BlockRegistry, BlockStateParser};
+
+    struct Blocks;
+
+    impl Registry for Blocks {
+        fn contains(&self, location: &ResourceLocation) -> bool {
+            self.locations().contains(location)
+        }
+
+        fn locations(&self) -> Vec<ResourceLocation> {
+            vec![
+                ResourceLocation::minecraft("stone").unwrap(),
+                ResourceLocation::minecraft("oak_stairs").unwrap(),
+            ]
+        }
+    }
+
+    impl BlockRegistry for Blocks {
+        fn property_values(&self, block: &ResourceLocation, property: &str) -> Option<Vec<String>> {
+            let values: &[&str] = match (block.path.as_str(), property) {
+                ("oak_stairs", "facing") => &["north", "east", "south", "west"],
+                ("oak_stairs", "half") => &["top", "bottom"],
+                _ => return None,
+            };
+            Some(values.iter().map(|v| String::from(*v)).collect())
+        }
+
+        fn contains_tag(&self, tag: &ResourceLocation) -> bool {
+            tag.path == "logs"
+        }
+    }
+
+    struct Game;
+
+    impl Registries for Game {
+        fn blocks(&self) -> Option<&dyn BlockRegistry> {
+            Some(&Blocks)
+        }
+    }
+
+    #[test]
+    fn block_state() {
+        let parser = BlockStateParser;
+        let ((state,), out) = parser
+            .parse(
+                (),
+                "minecraft:oak_stairs[facing=north, half=top]{Lock:\"a\"} 5",
+            )
+            .0
+            .unwrap();
+        assert_eq!(out, " 5");
+        assert_eq!(
+            state.block,
+            ResourceLocation::minecraft("oak_stairs").unwrap()
+        );
+        assert_eq!(state.properties["facing"], "north");
+        assert_eq!(state.properties["half"], "top");
+        let mut nbt = IndexMap::new();
+        nbt.insert(String::from("Lock"), Nbt::String(String::from("a")));
+        assert_eq!(state.nbt, Some(Nbt::Compound(nbt)));
+
+        for input in &["stone[", "stone[a]", "stone[a=b,a=c]", "stone[a=b", "#logs"] {
+            assert!(
+                !matches!(parser.parse((), input).0, Ok((_, ""))),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn registry() {
+        let parser = BlockStateParser;
+        assert!(parser
+            .parse_in(&Game, (), "oak_stairs[half=bottom]")
+            .0
+            .is_ok());
+        assert!(parser.parse_in(&Game, (), "stone").0.is_ok());
+        assert!(parser.parse_in(&Game, (), "dirt").0.is_err());
+        assert!(parser.parse((), "dirt").0.is_ok());
+        assert!(parser
+            .parse_in(&Game, (), "oak_stairs[half=middle]")
+            .0
+            .is_err());
+        assert!(parser.parse_in(&Game, (), "stone[half=top]").0.is_err());
+
+        let parser = BlockPredicateParser;
+        let ((predicate,), _) = parser
+            .parse_in(&Game, (), "#minecraft:logs[axis=y]")
+            .0
+            .unwrap();
+        assert!(matches!(predicate, BlockPredicate::Tag { .. }));
+        assert!(parser.parse_in(&Game, (), "#planks").0.is_err());
+        assert!(parser.parse((), "#planks").0.is_ok());
+        let ((predicate,), _) = parser.parse_in(&Game, (), "oak_stairs").0.unwrap();
+        assert!(matches!(predicate, BlockPredicate::Block(_)));
+    }
+
+    #[test]
+    fn regex() {
+        let dfa = DFA::<usize>::from(NFA::<usize>::regex(&BlockPredicateParser.regex()).unwrap());
+        for input in &[
+            "stone",
+            "#minecraft:logs[axis=y]",
+            "oak_stairs[facing=north]{a:1}",
+        ] {
+            assert!(dfa.find(input).is_ok(), "{}", input);
+        }
+    }
+}
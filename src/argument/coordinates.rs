@@ -0,0 +1,488 @@
+use anyhow::{anyhow, bail, Result};
+
+use crate::brigadier::{ArgumentType, NodeKind};
+use crate::feedback::Message;
+use crate::parser::{IterParser, Separator, Suggestion};
+
+use super::Argument;
+
+/// The position and rotation coordinates are resolved against, usually the executor of the command.
+/// The game state implements this for whatever it passes to the handler.
+pub trait CoordinateSource {
+    fn position(&self) -> [f64; 3];
+    /// Yaw and pitch in degrees, using the same conventions as Minecraft.
+    fn rotation(&self) -> [f32; 2];
+}
+
+/// A single component of a world coordinate, either absolute like `10` or relative like `~5`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorldCoordinate {
+    Absolute(f64),
+    Relative(f64),
+}
+
+impl WorldCoordinate {
+    pub fn resolve(&self, origin: f64) -> f64 {
+        match self {
+            WorldCoordinate::Absolute(value) => *value,
+            WorldCoordinate::Relative(offset) => origin + offset,
+        }
+    }
+}
+
+/// Three coordinates that have not yet been resolved against a source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinates {
+    /// Absolute and relative coordinates like `10 ~ ~-2`, in x y z order.
+    World([WorldCoordinate; 3]),
+    /// Local coordinates like `^ ^1 ^`, relative to the rotation of the source.
+    Local { left: f64, up: f64, forwards: f64 },
+}
+
+impl Coordinates {
+    pub fn resolve<S: CoordinateSource + ?Sized>(&self, source: &S) -> [f64; 3] {
+        let [x, y, z] = source.position();
+        match self {
+            Coordinates::World([cx, cy, cz]) => [cx.resolve(x), cy.resolve(y), cz.resolve(z)],
+            Coordinates::Local { left, up, forwards } => {
+                // Same as Minecraft: yaw 0 looks towards positive z, and positive pitch looks down.
+                let [yaw, pitch] = source.rotation();
+                let (yaw, pitch) = (f64::from(yaw).to_radians(), f64::from(pitch).to_radians());
+                let half_pi = std::f64::consts::FRAC_PI_2;
+
+                let forward_vec = [
+                    (yaw + half_pi).cos() * (-pitch).cos(),
+                    (-pitch).sin(),
+                    (yaw + half_pi).sin() * (-pitch).cos(),
+                ];
+                let up_vec = [
+                    (yaw + half_pi).cos() * (half_pi - pitch).cos(),
+                    (half_pi - pitch).sin(),
+                    (yaw + half_pi).sin() * (half_pi - pitch).cos(),
+                ];
+                // left = -(forward x up)
+                let left_vec = [
+                    forward_vec[2] * up_vec[1] - forward_vec[1] * up_vec[2],
+                    forward_vec[0] * up_vec[2] - forward_vec[2] * up_vec[0],
+                    forward_vec[1] * up_vec[0] - forward_vec[0] * up_vec[1],
+                ];
+
+                let offset =
+                    |i: usize| forward_vec[i] * forwards + up_vec[i] * up + left_vec[i] * left;
+                [x + offset(0), y + offset(1), z + offset(2)]
+            }
+        }
+    }
+}
+
+/// A position like `~ ~1.5 ~` or `^ ^ ^2`. Absolute x and z values without a decimal point are
+/// moved to the center of the block, like Minecraft does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vec3(pub Coordinates);
+
+impl Vec3 {
+    pub fn resolve<S: CoordinateSource + ?Sized>(&self, source: &S) -> [f64; 3] {
+        self.0.resolve(source)
+    }
+}
+
+/// A block position like `10 ~ ~-2`. Absolute values have to be integers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockPos(pub Coordinates);
+
+impl BlockPos {
+    pub fn resolve<S: CoordinateSource + ?Sized>(&self, source: &S) -> [i32; 3] {
+        let [x, y, z] = self.0.resolve(source);
+        [x.floor() as i32, y.floor() as i32, z.floor() as i32]
+    }
+}
+
+/// A horizontal position like `~ 10`, in x z order. Local coordinates are not allowed, like in Minecraft.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vec2 {
+    pub x: WorldCoordinate,
+    pub z: WorldCoordinate,
+}
+
+impl Vec2 {
+    pub fn resolve<S: CoordinateSource + ?Sized>(&self, source: &S) -> [f64; 2] {
+        let [x, _, z] = source.position();
+        [self.x.resolve(x), self.z.resolve(z)]
+    }
+}
+
+enum Component {
+    /// The bool is true if the value was written without a decimal point.
+    World(WorldCoordinate, bool),
+    Local(f64),
+}
+
+fn component<'i>(input: &'i str, separator: &Separator) -> Result<(Component, &'i str)> {
+    let (prefix, rest) = match input.chars().next() {
+        Some(c @ '~') | Some(c @ '^') => (Some(c), &input[1..]),
+        Some(_) => (None, input),
+        None => bail!(Message::translatable("argument.pos.missing.double", vec![])),
+    };
+
+    let end = rest
+        .find(|c| separator.is_separator(c))
+        .unwrap_or(rest.len());
+    let (number, out) = rest.split_at(end);
+
+    let value = if number.is_empty() {
+        if prefix.is_none() {
+            bail!(Message::translatable("argument.pos.missing.double", vec![]));
+        }
+        0.0
+    } else {
+        // f64::from_str also accepts things like "inf" and "1e5", which are not coordinates.
+        if !number
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '-' || c == '+')
+        {
+            bail!(Message::translatable(
+                "parsing.double.invalid",
+                vec![Message::text(number)]
+            ));
+        }
+        number.parse::<f64>().map_err(|_| {
+            anyhow!(Message::translatable(
+                "parsing.double.invalid",
+                vec![Message::text(number)]
+            ))
+        })?
+    };
+
+    let component = match prefix {
+        Some('~') => Component::World(WorldCoordinate::Relative(value), false),
+        Some(_) => Component::Local(value),
+        None => Component::World(WorldCoordinate::Absolute(value), !number.contains('.')),
+    };
+    Ok((component, out))
+}
+
+fn components<'i>(
+    input: &'i str,
+    count: usize,
+    separator: &Separator,
+) -> Result<(Vec<Component>, &'i str)> {
+    let mut out = input;
+    let mut parsed = Vec::with_capacity(count);
+    for i in 0..count {
+        if i != 0 {
+            out = separator.strip(out).ok_or_else(|| {
+                let key = if count == 2 {
+                    "argument.pos2d.incomplete"
+                } else {
+                    "argument.pos3d.incomplete"
+                };
+                anyhow!(Message::translatable(key, vec![]))
+            })?;
+        }
+        let (component, rest) = component(out, separator)?;
+        parsed.push(component);
+        out = rest;
+    }
+
+    let locals = parsed
+        .iter()
+        .filter(|c| matches!(c, Component::Local(_)))
+        .count();
+    if locals != 0 && locals != count {
+        bail!(Message::translatable("argument.pos.mixed", vec![]));
+    }
+
+    Ok((parsed, out))
+}
+
+/// Parses three components into coordinates. `world` maps every world component, which is how the
+/// different argument types apply their own rules to absolute values.
+fn coordinates<'i, F>(
+    input: &'i str,
+    separator: &Separator,
+    mut world: F,
+) -> Result<(Coordinates, &'i str)>
+where
+    F: FnMut(usize, WorldCoordinate, bool) -> Result<WorldCoordinate>,
+{
+    let (parsed, out) = components(input, 3, separator)?;
+    let coordinates = match parsed[..] {
+        [Component::Local(left), Component::Local(up), Component::Local(forwards)] => {
+            Coordinates::Local { left, up, forwards }
+        }
+        [Component::World(x, xi), Component::World(y, yi), Component::World(z, zi)] => {
+            Coordinates::World([world(0, x, xi)?, world(1, y, yi)?, world(2, z, zi)?])
+        }
+        _ => unreachable!("components checks that coordinates are not mixed"),
+    };
+    Ok((coordinates, out))
+}
+
+fn component_regex() -> &'static str {
+    "[~\\^]?[\\+-]?\\d*(\\.\\d*)?"
+}
+
+/// The regex of `count` components.
+fn components_regex(count: usize, separator: &Separator) -> String {
+    vec![format!("({})", component_regex()); count].join(&separator.regex())
+}
+
+fn suggest_relative(input: &str, count: usize, separator: &Separator) -> Vec<Suggestion> {
+    if input.trim().is_empty() {
+        vec![Suggestion {
+            start: input.len(),
+            text: vec!["~"; count].join(&separator.canonical().to_string()),
+        }]
+    } else {
+        Vec::new()
+    }
+}
+
+#[derive(Default)]
+pub struct Vec3Parser {
+    separator: Separator,
+}
+
+impl IterParser for Vec3Parser {
+    type Extract = (Vec3,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        let result = coordinates(input, &self.separator, |axis, coordinate, integer| {
+            Ok(match coordinate {
+                WorldCoordinate::Absolute(value) if integer && axis != 1 => {
+                    WorldCoordinate::Absolute(value + 0.5)
+                }
+                coordinate => coordinate,
+            })
+        });
+        (result.map(|(c, out)| ((Vec3(c),), out)), None)
+    }
+
+    fn regex(&self) -> String {
+        components_regex(3, &self.separator)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument("pos", ArgumentType::Vec3)]]
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        suggest_relative(input, 3, &self.separator)
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.separator = separator.clone();
+    }
+}
+
+impl Argument for Vec3 {
+    type Parser = Vec3Parser;
+    type ParserState = ();
+}
+
+#[derive(Default)]
+pub struct BlockPosParser {
+    separator: Separator,
+}
+
+impl IterParser for BlockPosParser {
+    type Extract = (BlockPos,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        let result = coordinates(
+            input,
+            &self.separator,
+            |_, coordinate, integer| match coordinate {
+                WorldCoordinate::Absolute(value) if !integer => {
+                    Err(anyhow!(Message::translatable(
+                        "parsing.int.invalid",
+                        vec![Message::text(value.to_string())]
+                    )))
+                }
+                coordinate => Ok(coordinate),
+            },
+        );
+        (result.map(|(c, out)| ((BlockPos(c),), out)), None)
+    }
+
+    fn regex(&self) -> String {
+        components_regex(3, &self.separator)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument("pos", ArgumentType::BlockPos)]]
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        suggest_relative(input, 3, &self.separator)
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.separator = separator.clone();
+    }
+}
+
+impl Argument for BlockPos {
+    type Parser = BlockPosParser;
+    type ParserState = ();
+}
+
+#[derive(Default)]
+pub struct Vec2Parser {
+    separator: Separator,
+}
+
+impl IterParser for Vec2Parser {
+    type Extract = (Vec2,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        let result =
+            components(input, 2, &self.separator).and_then(|(parsed, out)| match parsed[..] {
+                [Component::World(x, xi), Component::World(z, zi)] => {
+                    let center = |c, integer| match c {
+                        WorldCoordinate::Absolute(value) if integer => {
+                            WorldCoordinate::Absolute(value + 0.5)
+                        }
+                        c => c,
+                    };
+                    Ok((
+                        (Vec2 {
+                            x: center(x, xi),
+                            z: center(z, zi),
+                        },),
+                        out,
+                    ))
+                }
+                _ => Err(anyhow!(Message::translatable("argument.pos.mixed", vec![]))),
+            });
+        (result, None)
+    }
+
+    fn regex(&self) -> String {
+        components_regex(2, &self.separator)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument("pos", ArgumentType::Vec2)]]
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        suggest_relative(input, 2, &self.separator)
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.separator = separator.clone();
+    }
+}
+
+impl Argument for Vec2 {
+    type Parser = Vec2Parser;
+    type ParserState = ();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{IterParser, Separator};
+    use crate::regex::{DFA, NFA};
+
+    use super::*;
+
+    struct Source;
+
+    impl CoordinateSource for Source {
+        fn position(&self) -> [f64; 3] {
+            [10.0, 64.0, -3.0]
+        }
+
+        fn rotation(&self) -> [f32; 2] {
+            [0.0, 0.0]
+        }
+    }
+
+    fn close(a: [f64; 3], b: [f64; 3]) -> bool {
+        a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < 1e-9)
+    }
+
+    #[test]
+    fn world() {
+        let ((pos,), out) = Vec3Parser::default().parse((), "1 ~ ~-2.5 rest").0.unwrap();
+        assert_eq!(out, " rest");
+        assert!(close(pos.resolve(&Source), [1.5, 64.0, -5.5]));
+
+        let ((pos,), _) = BlockPosParser::default().parse((), "~1 70 ~").0.unwrap();
+        assert_eq!(pos.resolve(&Source), [11, 70, -3]);
+        assert!(BlockPosParser::default().parse((), "1.5 70 ~").0.is_err());
+
+        let ((pos,), _) = Vec2Parser::default().parse((), "~ 4").0.unwrap();
+        assert_eq!(pos.resolve(&Source), [10.0, 4.5]);
+    }
+
+    #[test]
+    fn local() {
+        let ((pos,), _) = Vec3Parser::default().parse((), "^1 ^ ^2").0.unwrap();
+        // Facing positive z, left is positive x.
+        assert!(close(pos.resolve(&Source), [11.0, 64.0, -1.0]));
+        assert!(Vec2Parser::default().parse((), "^ ^").0.is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        for input in &["^ ~ ^", "1 2", "1 2 x", "~~ 1 2", "1e5 1 1", "", "1  2  3a"] {
+            assert!(
+                Vec3Parser::default().parse((), input).0.is_err(),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn regex() {
+        let dfa = DFA::<usize>::from(NFA::<usize>::regex(&Vec3Parser::default().regex()).unwrap());
+        for input in &["1 ~ ~-2.5", "^ ^1 ^", "~ ~ ~"] {
+            assert!(dfa.find(input).is_ok(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn separator() {
+        let separator = Separator::chars([',']).unwrap();
+        let mut parser = Vec3Parser::default();
+        parser.set_separator(&separator);
+        assert_eq!(parser.parse((), "1,~2,3.5,5").0.unwrap().1, ",5");
+        assert!(parser.parse((), "1 2 3").0.is_err());
+        assert_eq!(
+            parser.suggest(""),
+            vec![Suggestion {
+                start: 0,
+                text: String::from("~,~,~")
+            }]
+        );
+
+        let mut parser = BlockPosParser::default();
+        parser.set_separator(&separator);
+        assert_eq!(parser.parse((), "1,2,3,5").0.unwrap().1, ",5");
+
+        let mut parser = Vec2Parser::default();
+        parser.set_separator(&separator);
+        assert_eq!(parser.parse((), "1,~2,5").0.unwrap().1, ",5");
+    }
+}
@@ -0,0 +1,260 @@
+use anyhow::{anyhow, bail, Result};
+
+use crate::brigadier::{ArgumentType, NodeKind, StringKind, ASK_SERVER};
+use crate::feedback::Message;
+use crate::parser::{IterParser, Suggestion};
+
+use super::Argument;
+
+pub const TICKS_PER_SECOND: u64 = 20;
+
+/// The units a duration can be written in, with how many ticks they are. A number without unit is in ticks.
+const UNITS: &[(char, u64)] = &[('t', 1), ('s', TICKS_PER_SECOND), ('d', 24_000)];
+
+/// Units vanilla does not have, see [`DurationParser::with_real_time_units`].
+const REAL_TIME_UNITS: &[(char, u64)] = &[
+    ('m', 60 * TICKS_PER_SECOND),
+    ('h', 60 * 60 * TICKS_PER_SECOND),
+];
+
+/// An amount of game time like `30s` or `1d`, counted in ticks. A day is a Minecraft day of 24000 ticks,
+/// seconds are real time at 20 ticks per second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration {
+    pub ticks: u64,
+}
+
+impl Duration {
+    pub fn from_ticks(ticks: u64) -> Self {
+        Self { ticks }
+    }
+
+    /// The real time the duration takes when the game runs at full speed.
+    pub fn to_std(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.ticks.saturating_mul(1000) / TICKS_PER_SECOND)
+    }
+}
+
+impl From<Duration> for std::time::Duration {
+    fn from(duration: Duration) -> Self {
+        duration.to_std()
+    }
+}
+
+/// Parses a [`Duration`], rejecting durations outside of `min..=max` ticks.
+pub struct DurationParser {
+    min: u64,
+    max: u64,
+    real_time_units: bool,
+}
+
+impl DurationParser {
+    /// A parser for the units of `minecraft:time`: `t`, `s` and `d`.
+    pub fn new(min: u64, max: u64) -> Self {
+        Self {
+            min,
+            max,
+            real_time_units: false,
+        }
+    }
+
+    /// A parser that also accepts minutes and hours, like `5m`. The client does not know these units, so
+    /// the argument is sent to it as a word it asks the server to complete.
+    pub fn with_real_time_units(min: u64, max: u64) -> Self {
+        Self {
+            real_time_units: true,
+            ..Self::new(min, max)
+        }
+    }
+
+    fn units(&self) -> impl Iterator<Item = &(char, u64)> {
+        let real_time: &[(char, u64)] = if self.real_time_units {
+            REAL_TIME_UNITS
+        } else {
+            &[]
+        };
+        UNITS.iter().chain(real_time)
+    }
+}
+
+impl Default for DurationParser {
+    fn default() -> Self {
+        Self::new(0, i32::MAX as u64)
+    }
+}
+
+/// Returns the length of the number at the start of the input.
+fn number_len(input: &str) -> usize {
+    input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len())
+}
+
+impl IterParser for DurationParser {
+    type Extract = (Duration,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        let parse = || {
+            if input.starts_with('-') {
+                bail!(Message::translatable(
+                    "argument.time.invalid_tick_count",
+                    vec![]
+                ));
+            }
+            let end = number_len(input);
+            let value = input[..end]
+                .parse::<f64>()
+                .map_err(|_| anyhow!(Message::translatable("parsing.float.expected", vec![])))?;
+
+            let (multiplier, end) = match input[end..].chars().next() {
+                Some(c) if c.is_alphabetic() => {
+                    let (_, multiplier) =
+                        self.units().find(|(unit, _)| *unit == c).ok_or_else(|| {
+                            anyhow!(Message::translatable("argument.time.invalid_unit", vec![]))
+                        })?;
+                    (*multiplier, end + c.len_utf8())
+                }
+                _ => (1, end),
+            };
+
+            let ticks = (value * multiplier as f64).round();
+            if ticks < self.min as f64 {
+                bail!(Message::translatable(
+                    "argument.time.tick_count_too_low",
+                    vec![
+                        Message::text(self.min.to_string()),
+                        Message::text(ticks.to_string())
+                    ]
+                ));
+            }
+            if ticks > self.max as f64 {
+                bail!(Message::translatable(
+                    "lieutenant.time.tick_count_too_high",
+                    vec![
+                        Message::text(self.max.to_string()),
+                        Message::text(ticks.to_string())
+                    ]
+                ));
+            }
+
+            Ok(((Duration::from_ticks(ticks as u64),), &input[end..]))
+        };
+        (parse(), None)
+    }
+
+    fn regex(&self) -> String {
+        let units = self.units().map(|(unit, _)| *unit).collect::<String>();
+        format!("(\\d+(\\.\\d*)?|\\.\\d+)[{}]?", units)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        let node = if self.real_time_units {
+            NodeKind::argument("time", ArgumentType::String(StringKind::SingleWord))
+                .with_suggestions(ASK_SERVER)
+        } else {
+            NodeKind::argument("time", ArgumentType::Time)
+        };
+        vec![vec![node]]
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        let end = number_len(input);
+        if end == 0 || end != input.len() {
+            return Vec::new();
+        }
+        self.units()
+            .map(|(unit, _)| Suggestion {
+                start: end,
+                text: unit.to_string(),
+            })
+            .collect()
+    }
+}
+
+impl Argument for Duration {
+    type Parser = DurationParser;
+    type ParserState = ();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brigadier::{ArgumentType, NodeKind, StringKind, ASK_SERVER};
+    use crate::parser::IterParser;
+
+    use super::{Duration, DurationParser};
+
+    fn ticks(parser: &DurationParser, input: &str) -> anyhow::Result<u64> {
+        let ((duration,), out) = parser.parse((), input).0?;
+        assert_eq!(out, "");
+        Ok(duration.ticks)
+    }
+
+    #[test]
+    fn units() {
+        let parser = DurationParser::default();
+        assert_eq!(ticks(&parser, "15").unwrap(), 15);
+        assert_eq!(ticks(&parser, "15t").unwrap(), 15);
+        assert_eq!(ticks(&parser, "1.5s").unwrap(), 30);
+        assert_eq!(ticks(&parser, "0.5d").unwrap(), 12000);
+        assert!(ticks(&parser, "2m").is_err());
+        assert_eq!(
+            Duration::from_ticks(30).to_std(),
+            std::time::Duration::from_millis(1500)
+        );
+        assert_eq!(
+            Duration::from_ticks(u64::MAX).to_std(),
+            std::time::Duration::from_millis(u64::MAX / 20)
+        );
+
+        let ((_,), out) = parser.parse((), "30s speed").0.unwrap();
+        assert_eq!(out, " speed");
+    }
+
+    #[test]
+    fn real_time_units() {
+        let parser = DurationParser::with_real_time_units(0, u64::MAX);
+        assert_eq!(ticks(&parser, "2m").unwrap(), 2400);
+        assert_eq!(ticks(&parser, "1h").unwrap(), 72000);
+        assert_eq!(ticks(&parser, "1d").unwrap(), 24000);
+        assert_eq!(parser.suggest("30").len(), 5);
+
+        // The client only knows the units of `minecraft:time`, so it has to ask the server.
+        assert_eq!(
+            parser.brigadier(),
+            vec![vec![NodeKind::argument(
+                "time",
+                ArgumentType::String(StringKind::SingleWord)
+            )
+            .with_suggestions(ASK_SERVER)]]
+        );
+        assert_eq!(
+            DurationParser::default().brigadier(),
+            vec![vec![NodeKind::argument("time", ArgumentType::Time)]]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let parser = DurationParser::new(20, 100);
+        for input in &["-1s", "10x", "s", "1.2.3", "19t", "6s"] {
+            assert!(parser.parse((), input).0.is_err(), "{}", input);
+        }
+        assert_eq!(ticks(&parser, "5s").unwrap(), 100);
+    }
+
+    #[test]
+    fn suggest() {
+        let parser = DurationParser::default();
+        let suggestions = parser.suggest("30");
+        assert_eq!(suggestions.len(), 3);
+        assert!(suggestions.iter().all(|s| s.start == 2));
+        assert!(parser.suggest("30s").is_empty());
+        assert!(parser.suggest("").is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::{bail, Result};
+
+use crate::brigadier::{ArgumentType, NodeKind};
+use crate::feedback::Message;
+use crate::parser::IterParser;
+
+use super::{
+    block::trailing_nbt, resource::resource_location, Argument, Nbt, Registries, Registry,
+    ResourceLocation,
+};
+
+/// The items of the game, used to validate item stacks.
+pub trait ItemRegistry: Registry {
+    fn contains_tag(&self, tag: &ResourceLocation) -> bool;
+}
+
+/// An item with optional data, like `diamond_sword{Damage:10}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStack {
+    pub item: ResourceLocation,
+    pub nbt: Option<Nbt>,
+}
+
+/// Matches items either by id like an [`ItemStack`], or by a tag like `#minecraft:logs`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemPredicate {
+    Item(ItemStack),
+    Tag {
+        tag: ResourceLocation,
+        nbt: Option<Nbt>,
+    },
+}
+
+const ITEM_REGEX: &str = "([a-z0-9_\\.\\-]*:)?[a-z0-9_\\./\\-]+(\\{.*\\})?";
+
+fn item_stack<'p>(
+    registry: Option<&dyn ItemRegistry>,
+    input: &'p str,
+) -> Result<(ItemStack, &'p str)> {
+    let (item, out) = resource_location(input)?;
+    let (nbt, out) = trailing_nbt(out)?;
+    if let Some(registry) = registry {
+        if !registry.contains(&item) {
+            bail!(Message::translatable(
+                "argument.item.id.invalid",
+                vec![Message::text(item.to_string())]
+            ));
+        }
+    }
+    Ok((ItemStack { item, nbt }, out))
+}
+
+/// Parses an [`ItemStack`]. Unless the [`Registries`] have items, any well formed item is accepted.
+#[derive(Default)]
+pub struct ItemStackParser;
+
+impl IterParser for ItemStackParser {
+    type Extract = (ItemStack,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        self.parse_in(&(), state, input)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn parse_in<'p>(
+        &self,
+        registries: &dyn Registries,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        let result = item_stack(registries.items(), input);
+        (result.map(|(stack, out)| ((stack,), out)), None)
+    }
+
+    fn regex(&self) -> String {
+        String::from(ITEM_REGEX)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument("item", ArgumentType::ItemStack)]]
+    }
+}
+
+impl Argument for ItemStack {
+    type Parser = ItemStackParser;
+    type ParserState = ();
+}
+
+/// Parses an [`ItemPredicate`].
+#[derive(Default)]
+pub struct ItemPredicateParser;
+
+impl IterParser for ItemPredicateParser {
+    type Extract = (ItemPredicate,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        self.parse_in(&(), state, input)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn parse_in<'p>(
+        &self,
+        registries: &dyn Registries,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        let parse = || {
+            let input = match input.strip_prefix('#') {
+                Some(input) => input,
+                None => {
+                    let (stack, out) = item_stack(registries.items(), input)?;
+                    return Ok(((ItemPredicate::Item(stack),), out));
+                }
+            };
+
+            let (tag, out) = resource_location(input)?;
+            let (nbt, out) = trailing_nbt(out)?;
+            if let Some(registry) = registries.items() {
+                if !registry.contains_tag(&tag) {
+                    bail!(Message::translatable(
+                        "arguments.item.tag.unknown",
+                        vec![Message::text(tag.to_string())]
+                    ));
+                }
+            }
+            Ok(((ItemPredicate::Tag { tag, nbt },), out))
+        };
+        (parse(), None)
+    }
+
+    fn regex(&self) -> String {
+        format!("#?{}", ITEM_REGEX)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument(
+            "predicate",
+            ArgumentType::ItemPredicate,
+        )]]
+    }
+}
+
+impl Argument for ItemPredicate {
+    type Parser = ItemPredicateParser;
+    type ParserState = ();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::argument::{Registries, Registry, ResourceLocation};
+    use crate::parser::IterParser;
+
+    use super::{ItemPredicate, ItemPredicateParser, ItemRegistry, ItemStackParser};
+
+    struct Items;
+
+    impl Registry for Items {
+        fn contains(&self, location: &ResourceLocation) -> bool {
+            location.path == "diamond_sword"
+        }
+
+        fn locations(&self) -> Vec<ResourceLocation> {
+            vec![ResourceLocation::minecraft("diamond_sword").unwrap()]
+        }
+    }
+
+    impl ItemRegistry for Items {
+        fn contains_tag(&self, tag: &ResourceLocation) -> bool {
+            tag.path == "logs"
+        }
+    }
+
+    struct Game;
+
+    impl Registries for Game {
+        fn items(&self) -> Option<&dyn ItemRegistry> {
+            Some(&Items)
+        }
+    }
+
+    #[test]
+    fn item_stack() {
+        let parser = ItemStackParser;
+        let ((stack,), out) = parser
+            .parse_in(
+                &Game,
+                (),
+                "diamond_sword{Enchantments:[{id:sharpness,lvl:5s}]} 1",
+            )
+            .0
+            .unwrap();
+        assert_eq!(out, " 1");
+        assert_eq!(stack.item.to_string(), "minecraft:diamond_sword");
+        assert!(stack.nbt.is_some());
+        assert!(parser.parse_in(&Game, (), "stone").0.is_err());
+        assert!(parser.parse((), "stone").0.is_ok());
+        assert!(parser.parse_in(&Game, (), "diamond_sword{").0.is_err());
+    }
+
+    #[test]
+    fn predicate() {
+        let parser = ItemPredicateParser;
+        let ((predicate,), _) = parser.parse_in(&Game, (), "#logs{a:1b}").0.unwrap();
+        assert!(matches!(predicate, ItemPredicate::Tag { nbt: Some(_), .. }));
+        assert!(parser.parse_in(&Game, (), "#planks").0.is_err());
+        assert!(matches!(
+            parser.parse_in(&Game, (), "diamond_sword").0.unwrap().0 .0,
+            ItemPredicate::Item(_)
+        ));
+    }
+}
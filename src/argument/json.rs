@@ -0,0 +1,401 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+
+use crate::brigadier::{ArgumentType, NodeKind};
+use crate::feedback::Message;
+use crate::parser::IterParser;
+
+use super::Argument;
+
+/// A JSON value, like the text components `/tellraw` and `/title` take.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(IndexMap<String, Json>),
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+/// Writes the value as compact JSON.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(value) => write_string(f, value),
+            Json::Array(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(values) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in values.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Keeps deeply nested input from overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+struct Reader<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, message: &str) -> anyhow::Error {
+        anyhow!(Message::translatable(
+            "argument.component.invalid",
+            vec![Message::text(format!(
+                "{} at position {}",
+                message, self.pos
+            ))]
+        ))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json> {
+        if self.rest().starts_with(keyword) {
+            self.pos += keyword.len();
+            Ok(value)
+        } else {
+            Err(self.error("Expected a value"))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("JSON is nested too deeply"));
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth),
+            Some('"') => self.string().map(Json::String),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('n') => self.keyword("null", Json::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Json> {
+        self.pos += 1;
+        let mut values = IndexMap::new();
+        if self.eat('}') {
+            return Ok(Json::Object(values));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("Expected a key"));
+            }
+            let key = self.string()?;
+            if !self.eat(':') {
+                return Err(self.error("Expected ':'"));
+            }
+            values.insert(key, self.value(depth + 1)?);
+
+            if self.eat('}') {
+                return Ok(Json::Object(values));
+            }
+            if !self.eat(',') {
+                return Err(self.error("Expected ',' or '}'"));
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Json> {
+        self.pos += 1;
+        let mut values = Vec::new();
+        if self.eat(']') {
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value(depth + 1)?);
+
+            if self.eat(']') {
+                return Ok(Json::Array(values));
+            }
+            if !self.eat(',') {
+                return Err(self.error("Expected ',' or ']'"));
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32> {
+        let digits = self.rest().get(..4).unwrap_or("");
+        match u32::from_str_radix(digits, 16) {
+            Ok(value) if digits.len() == 4 && digits.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                self.pos += 4;
+                Ok(value)
+            }
+            _ => Err(self.error("Expected four hex digits")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut value = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(value),
+                '\\' => {
+                    let escape = self.peek().ok_or_else(|| self.error("Unclosed string"))?;
+                    self.pos += escape.len_utf8();
+                    match escape {
+                        '"' | '\\' | '/' => value.push(escape),
+                        'b' => value.push('\u{8}'),
+                        'f' => value.push('\u{c}'),
+                        'n' => value.push('\n'),
+                        'r' => value.push('\r'),
+                        't' => value.push('\t'),
+                        'u' => {
+                            let mut code = self.hex()?;
+                            // Characters outside the basic plane are written as a surrogate pair.
+                            if (0xd800..0xdc00).contains(&code) && self.rest().starts_with("\\u") {
+                                self.pos += 2;
+                                let low = self.hex()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return Err(self.error("Invalid unicode escape"));
+                                }
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            value.push(
+                                std::char::from_u32(code)
+                                    .ok_or_else(|| self.error("Invalid unicode escape"))?,
+                            );
+                        }
+                        _ => {
+                            self.pos -= escape.len_utf8() + 1;
+                            return Err(self.error("Invalid escape sequence"));
+                        }
+                    }
+                }
+                c if c.is_control() => {
+                    self.pos -= c.len_utf8();
+                    return Err(self.error("Control character in string"));
+                }
+                c => value.push(c),
+            }
+        }
+
+        self.pos = start;
+        Err(self.error("Unclosed string"))
+    }
+
+    fn number(&mut self) -> Result<Json> {
+        let rest = self.rest();
+        let bytes = rest.as_bytes();
+        let digits = |mut i: usize| {
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            i
+        };
+
+        let mut end = if bytes[0] == b'-' { 1 } else { 0 };
+        let int_end = digits(end);
+        if int_end == end || (bytes[end] == b'0' && int_end > end + 1) {
+            return Err(self.error("Invalid number"));
+        }
+        end = int_end;
+        if bytes.get(end) == Some(&b'.') {
+            let fraction_end = digits(end + 1);
+            if fraction_end == end + 1 {
+                return Err(self.error("Invalid number"));
+            }
+            end = fraction_end;
+        }
+        if let Some(b'e') | Some(b'E') = bytes.get(end) {
+            let mut exponent = end + 1;
+            if let Some(b'+') | Some(b'-') = bytes.get(exponent) {
+                exponent += 1;
+            }
+            let exponent_end = digits(exponent);
+            if exponent_end == exponent {
+                return Err(self.error("Invalid number"));
+            }
+            end = exponent_end;
+        }
+
+        let value = rest[..end]
+            .parse()
+            .map_err(|_| self.error("Invalid number"))?;
+        self.pos += end;
+        Ok(Json::Number(value))
+    }
+}
+
+/// Reads exactly one JSON value from the start of the input and returns the rest.
+pub(crate) fn json(input: &str) -> Result<(Json, &str)> {
+    let mut reader = Reader { input, pos: 0 };
+    let value = reader.value(0)?;
+    Ok((value, reader.rest()))
+}
+
+#[derive(Default)]
+pub struct JsonParser;
+
+impl IterParser for JsonParser {
+    type Extract = (Json,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        (json(input).map(|(json, out)| ((json,), out)), None)
+    }
+
+    fn regex(&self) -> String {
+        // Nesting can't be described by a regex, so anything that starts and ends like an object or array is let through.
+        String::from(
+            "(\\{.*\\}|\\[.*\\]|\"([^\"\\\\]|\\\\.)*\"|-?\\d+(\\.\\d+)?([eE][\\+\\-]?\\d+)?|true|false|null)",
+        )
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument(
+            "component",
+            ArgumentType::Component,
+        )]]
+    }
+}
+
+impl Argument for Json {
+    type Parser = JsonParser;
+    type ParserState = ();
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::parser::IterParser;
+    use crate::regex::{DFA, NFA};
+
+    use super::{json, Json, JsonParser};
+
+    #[test]
+    fn values() {
+        let (value, out) = json(
+            "{\"text\": \"a\\\"b\\u00e9\\ud83d\\ude00\", \"extra\": [1, -2.5e1, true, null]} rest",
+        )
+        .unwrap();
+        assert_eq!(out, " rest");
+
+        let mut expected = IndexMap::new();
+        expected.insert(String::from("text"), Json::String(String::from("a\"bé😀")));
+        expected.insert(
+            String::from("extra"),
+            Json::Array(vec![
+                Json::Number(1.0),
+                Json::Number(-25.0),
+                Json::Bool(true),
+                Json::Null,
+            ]),
+        );
+        assert_eq!(value, Json::Object(expected));
+        assert_eq!(json(&value.to_string()).unwrap(), (value, ""));
+
+        let ((value,), out) = JsonParser.parse((), "\"hi\" @a").0.unwrap();
+        assert_eq!(value, Json::String(String::from("hi")));
+        assert_eq!(out, " @a");
+    }
+
+    #[test]
+    fn errors() {
+        for input in &[
+            "{\"a\" 1}",
+            "[1,]",
+            "{a:1}",
+            "\"abc",
+            "01",
+            "1.",
+            "\"\\x\"",
+            "tru",
+            "",
+        ] {
+            assert!(json(input).is_err(), "{}", input);
+        }
+        assert!(json(&"[".repeat(600)).is_err());
+    }
+
+    #[test]
+    fn regex() {
+        let dfa = DFA::<usize>::from(NFA::<usize>::regex(&JsonParser.regex()).unwrap());
+        for input in &[
+            "{\"text\":\"a b\"}",
+            "[1,2]",
+            "\"a\\\"b\"",
+            "-1.5e3",
+            "null",
+        ] {
+            assert!(dfa.find(input).is_ok(), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::anyhow;
+
+use crate::brigadier::NodeKind;
+use crate::feedback::Message;
+use crate::parser::{IterParser, Literal, Separator, Suggestion};
+
+/// A value that is parsed from one of a closed set of keywords, like the variants of
+/// `enum GameMode { Survival, Creative }`. The keywords are matched case insensitively like [`Literal`].
+/// Implementing this and setting the [`Argument::Parser`](super::Argument::Parser) to [`KeywordParser`] makes
+/// the type an argument. `#[derive(Argument)]` does both for enums.
+pub trait Keyword: Sized {
+    /// Every keyword, in the order they are tried.
+    fn keywords() -> &'static [&'static str];
+
+    /// Returns the value of the keyword at `index` in [`Keyword::keywords`], or `None` if there is no
+    /// keyword at `index`.
+    fn from_index(index: usize) -> Option<Self>;
+}
+
+pub struct KeywordParser<K> {
+    keywords: Vec<Literal>,
+    keyword: std::marker::PhantomData<fn() -> K>,
+}
+
+impl<K: Keyword> Default for KeywordParser<K> {
+    fn default() -> Self {
+        Self {
+            keywords: K::keywords()
+                .iter()
+                .map(|keyword| Literal::new(String::from(*keyword)))
+                .collect(),
+            keyword: Default::default(),
+        }
+    }
+}
+
+impl<K: Keyword> IterParser for KeywordParser<K> {
+    type Extract = (K,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (
+        anyhow::Result<(Self::Extract, &'p str)>,
+        Option<Self::ParserState>,
+    ) {
+        for (index, keyword) in self.keywords.iter().enumerate() {
+            if let (Ok(((), out)), _) = keyword.parse((), input) {
+                if let Some(value) = K::from_index(index) {
+                    return (Ok(((value,), out)), None);
+                }
+            }
+        }
+
+        (
+            Err(anyhow!(Message::translatable(
+                "argument.literal.incorrect",
+                vec![Message::text(K::keywords().join(", "))]
+            ))),
+            None,
+        )
+    }
+
+    fn regex(&self) -> String {
+        let keywords: Vec<String> = self.keywords.iter().map(|k| k.regex()).collect();
+        format!("({})", keywords.join("|"))
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        self.keywords.iter().flat_map(|k| k.brigadier()).collect()
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        self.keywords
+            .iter()
+            .flat_map(|keyword| keyword.suggest(input))
+            .collect()
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        for keyword in &mut self.keywords {
+            keyword.set_separator(separator);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::argument::Argument;
+    use crate::parser::{IterParser, Separator, Suggestion};
+
+    use super::{Keyword, KeywordParser};
+
+    #[derive(Debug, PartialEq)]
+    enum Difficulty {
+        Peaceful,
+        Easy,
+        Normal,
+        Hard,
+    }
+
+    impl Keyword for Difficulty {
+        fn keywords() -> &'static [&'static str] {
+            &["peaceful", "easy", "normal", "hard"]
+        }
+
+        fn from_index(index: usize) -> Option<Self> {
+            match index {
+                0 => Some(Self::Peaceful),
+                1 => Some(Self::Easy),
+                2 => Some(Self::Normal),
+                3 => Some(Self::Hard),
+                _ => None,
+            }
+        }
+    }
+
+    impl Argument for Difficulty {
+        type Parser = KeywordParser<Self>;
+        type ParserState = ();
+    }
+
+    #[test]
+    fn parse() {
+        let parser = <Difficulty as Argument>::Parser::default();
+
+        let (res, _) = parser.parse((), "Hard");
+        assert_eq!(res.unwrap(), ((Difficulty::Hard,), ""));
+        let (res, _) = parser.parse((), "easy now");
+        assert_eq!(res.unwrap(), ((Difficulty::Easy,), " now"));
+        assert!(parser.parse((), "easier").0.is_err());
+        assert_eq!(
+            parser.regex(),
+            "((?i:peaceful)|(?i:easy)|(?i:normal)|(?i:hard))"
+        );
+    }
+
+    #[test]
+    fn suggest() {
+        let parser = KeywordParser::<Difficulty>::default();
+
+        assert_eq!(parser.suggest("").len(), 4);
+        assert_eq!(
+            parser.suggest("N"),
+            vec![Suggestion {
+                start: 0,
+                text: String::from("normal")
+            }]
+        );
+        assert!(parser.suggest("normal x").is_empty());
+    }
+
+    #[test]
+    fn separator() {
+        let mut parser = KeywordParser::<Difficulty>::default();
+        parser.set_separator(&Separator::chars([',']).unwrap());
+        let ((difficulty,), out) = parser.parse((), "Hard,5").0.unwrap();
+        assert_eq!((difficulty, out), (Difficulty::Hard, ",5"));
+        assert!(parser.parse((), "hardest,5").0.is_err());
+    }
+}
@@ -1,8 +1,28 @@
+mod block;
+mod coordinates;
+mod duration;
+mod item;
+mod json;
+mod keyword;
+mod nbt;
 mod numbers;
+mod range;
+mod resource;
+mod selector;
 use crate::parser::IterParser;
+pub use block::*;
+pub use coordinates::*;
+pub use duration::*;
+pub use item::*;
+pub use json::*;
+pub use keyword::*;
+pub use nbt::*;
 pub use numbers::*;
+pub use range::*;
+pub use resource::*;
+pub use selector::*;
 
 pub trait Argument {
     type Parser: IterParser<Extract = (Self,), ParserState = Self::ParserState> + Sized + Default;
-    type ParserState: Default;
+    type ParserState: Default + Clone;
 }
@@ -0,0 +1,573 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+
+use crate::brigadier::{ArgumentType, NodeKind};
+use crate::feedback::Message;
+use crate::parser::{IterParser, Located};
+
+use super::Argument;
+
+/// An owned NBT value, parsed from its stringified form like `{Count:3b,Tags:["a","b"]}`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Nbt {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    List(Vec<Nbt>),
+    Compound(IndexMap<String, Nbt>),
+    ByteArray(Vec<i8>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Nbt {
+    fn kind(&self) -> &'static str {
+        match self {
+            Nbt::Byte(_) => "byte",
+            Nbt::Short(_) => "short",
+            Nbt::Int(_) => "int",
+            Nbt::Long(_) => "long",
+            Nbt::Float(_) => "float",
+            Nbt::Double(_) => "double",
+            Nbt::String(_) => "string",
+            Nbt::List(_) => "list",
+            Nbt::Compound(_) => "compound",
+            Nbt::ByteArray(_) => "byte array",
+            Nbt::IntArray(_) => "int array",
+            Nbt::LongArray(_) => "long array",
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    if !value.is_empty() && value.chars().all(unquoted_char) {
+        return f.write_str(value);
+    }
+    f.write_str("\"")?;
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            f.write_str("\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    f.write_str("\"")
+}
+
+fn write_list<T>(
+    f: &mut fmt::Formatter<'_>,
+    prefix: &str,
+    values: &[T],
+    write: impl Fn(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    write!(f, "[{}", prefix)?;
+    for (i, value) in values.iter().enumerate() {
+        if i != 0 {
+            f.write_str(",")?;
+        }
+        write(f, value)?;
+    }
+    f.write_str("]")
+}
+
+/// Writes the value back as SNBT, which parses to the same value.
+impl fmt::Display for Nbt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Nbt::Byte(v) => write!(f, "{}b", v),
+            Nbt::Short(v) => write!(f, "{}s", v),
+            Nbt::Int(v) => write!(f, "{}", v),
+            Nbt::Long(v) => write!(f, "{}L", v),
+            Nbt::Float(v) => write!(f, "{}f", v),
+            Nbt::Double(v) => write!(f, "{}d", v),
+            // Always quoted, so strings like "1b" or "true" stay strings.
+            Nbt::String(v) => write!(f, "\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\"")),
+            Nbt::List(values) => write_list(f, "", values, |f, v| write!(f, "{}", v)),
+            Nbt::Compound(values) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in values.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+            Nbt::ByteArray(values) => write_list(f, "B;", values, |f, v| write!(f, "{}b", v)),
+            Nbt::IntArray(values) => write_list(f, "I;", values, |f, v| write!(f, "{}", v)),
+            Nbt::LongArray(values) => write_list(f, "L;", values, |f, v| write!(f, "{}L", v)),
+        }
+    }
+}
+
+/// Same limit as Minecraft, which keeps deeply nested input from overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+fn unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '+'
+}
+
+/// Matches `[-+]?(0|[1-9][0-9]*)`.
+fn is_integer(s: &str) -> bool {
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    match digits.as_bytes() {
+        [b'0'] => true,
+        [first, rest @ ..] => {
+            *first != b'0' && first.is_ascii_digit() && rest.iter().all(u8::is_ascii_digit)
+        }
+        [] => false,
+    }
+}
+
+/// Matches `[-+]?([0-9]+[.]?|[0-9]*[.][0-9]+)(e[-+]?[0-9]+)?`, where the dot is required if `dot` is set.
+fn is_decimal(s: &str, dot: bool) -> bool {
+    let s = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(e) => (&s[..e], Some(&s[e + 1..])),
+        None => (s, None),
+    };
+    let (whole, fraction) = match mantissa.find('.') {
+        Some(d) => (&mantissa[..d], Some(&mantissa[d + 1..])),
+        None => (mantissa, None),
+    };
+
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let mantissa_ok = match fraction {
+        Some(fraction) => {
+            digits(whole) && digits(fraction) && !(whole.is_empty() && fraction.is_empty())
+        }
+        None => !dot && !whole.is_empty() && digits(whole),
+    };
+    let exponent_ok = match exponent {
+        Some(exponent) => {
+            let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+            !exponent.is_empty() && digits(exponent)
+        }
+        None => true,
+    };
+    mantissa_ok && exponent_ok
+}
+
+/// Decides the type of an unquoted value like Minecraft does. Numbers that are out of range become strings.
+fn typed(token: &str) -> Nbt {
+    let suffix = token.chars().last().map(|c| c.to_ascii_lowercase());
+    let body = &token[..token.len() - suffix.map_or(0, char::len_utf8)];
+    let number = match suffix {
+        Some('b') if is_integer(body) => body.parse().ok().map(Nbt::Byte),
+        Some('s') if is_integer(body) => body.parse().ok().map(Nbt::Short),
+        Some('l') if is_integer(body) => body.parse().ok().map(Nbt::Long),
+        Some('f') if is_decimal(body, false) => body.parse().ok().map(Nbt::Float),
+        Some('d') if is_decimal(body, false) => body.parse().ok().map(Nbt::Double),
+        _ if is_integer(token) => token.parse().ok().map(Nbt::Int),
+        _ if is_decimal(token, true) => token.parse().ok().map(Nbt::Double),
+        _ => None,
+    };
+
+    number.unwrap_or_else(|| match token {
+        "true" => Nbt::Byte(1),
+        "false" => Nbt::Byte(0),
+        _ => Nbt::String(String::from(token)),
+    })
+}
+
+struct Reader<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, key: &str, with: Vec<Message>) -> anyhow::Error {
+        anyhow::Error::new(Located {
+            position: self.pos,
+            error: anyhow!(Message::translatable(key, with)),
+        })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(
+                "parsing.expected",
+                vec![Message::text(expected.to_string())],
+            ))
+        }
+    }
+
+    fn unquoted(&mut self) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(|c| !unquoted_char(c)).unwrap_or(rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let start = self.pos;
+        let quote = self.peek().unwrap_or('"');
+        self.pos += 1;
+
+        let mut value = String::new();
+        let mut escaped = false;
+        while let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+            match c {
+                '\\' | '"' | '\'' if escaped => {
+                    value.push(c);
+                    escaped = false;
+                }
+                c if escaped => {
+                    self.pos -= c.len_utf8();
+                    return Err(
+                        self.error("parsing.quote.escape", vec![Message::text(c.to_string())])
+                    );
+                }
+                '\\' => escaped = true,
+                c if c == quote => return Ok(value),
+                c => value.push(c),
+            }
+        }
+
+        self.pos = start;
+        Err(self.error("parsing.quote.expected.end", vec![]))
+    }
+
+    fn key(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') | Some('\'') => self.quoted(),
+            _ => match self.unquoted() {
+                "" => Err(self.error("argument.nbt.expected.key", vec![])),
+                key => Ok(String::from(key)),
+            },
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Nbt> {
+        if depth > MAX_DEPTH {
+            return Err(self.error(
+                "lieutenant.nbt.depth",
+                vec![Message::text(MAX_DEPTH.to_string())],
+            ));
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.compound(depth),
+            Some('[') => self.list(depth),
+            Some('"') | Some('\'') => self.quoted().map(Nbt::String),
+            _ => match self.unquoted() {
+                "" => Err(self.error("argument.nbt.expected.value", vec![])),
+                token => Ok(typed(token)),
+            },
+        }
+    }
+
+    fn compound(&mut self, depth: usize) -> Result<Nbt> {
+        self.expect('{')?;
+        let mut values = IndexMap::new();
+        self.skip_whitespace();
+        while self.peek() != Some('}') {
+            let key = self.key()?;
+            self.expect(':')?;
+            let value = self.value(depth + 1)?;
+            values.insert(key, value);
+            if !self.separator('}')? {
+                break;
+            }
+        }
+        self.expect('}')?;
+        Ok(Nbt::Compound(values))
+    }
+
+    /// Skips a `,` and returns if another element follows.
+    fn separator(&mut self, end: char) -> Result<bool> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.peek() == Some(end) {
+                    return Err(self.error("argument.nbt.expected.value", vec![]));
+                }
+                Ok(true)
+            }
+            Some(c) if c == end => Ok(false),
+            _ => Err(self.error("parsing.expected", vec![Message::text(end.to_string())])),
+        }
+    }
+
+    fn list(&mut self, depth: usize) -> Result<Nbt> {
+        // Like in Minecraft, any `[X;` starts an array, so an unknown type is reported as such.
+        let mut chars = self.rest().chars().skip(1);
+        if let (Some(kind), Some(';')) = (chars.next(), chars.next()) {
+            if kind != '"' && kind != '\'' {
+                let array = match kind {
+                    'B' => Nbt::ByteArray(Vec::new()),
+                    'I' => Nbt::IntArray(Vec::new()),
+                    'L' => Nbt::LongArray(Vec::new()),
+                    _ => {
+                        self.pos += 1;
+                        return Err(self.error(
+                            "argument.nbt.array.invalid",
+                            vec![Message::text(kind.to_string())],
+                        ));
+                    }
+                };
+                self.pos += 3;
+                return self.array(array);
+            }
+        }
+
+        self.expect('[')?;
+        let mut values: Vec<Nbt> = Vec::new();
+        self.skip_whitespace();
+        while self.peek() != Some(']') {
+            let start = self.pos;
+            let value = self.value(depth + 1)?;
+            if let Some(first) = values.first() {
+                if std::mem::discriminant(first) != std::mem::discriminant(&value) {
+                    self.pos = start;
+                    return Err(self.error(
+                        "argument.nbt.list.mixed",
+                        vec![Message::text(value.kind()), Message::text(first.kind())],
+                    ));
+                }
+            }
+            values.push(value);
+            if !self.separator(']')? {
+                break;
+            }
+        }
+        self.expect(']')?;
+        Ok(Nbt::List(values))
+    }
+
+    fn array(&mut self, mut array: Nbt) -> Result<Nbt> {
+        self.skip_whitespace();
+        while self.peek() != Some(']') {
+            let start = self.pos;
+            let value = match self.unquoted() {
+                "" => return Err(self.error("argument.nbt.expected.value", vec![])),
+                token => typed(token),
+            };
+            match (&mut array, value) {
+                (Nbt::ByteArray(values), Nbt::Byte(v)) => values.push(v),
+                (Nbt::IntArray(values), Nbt::Int(v)) => values.push(v),
+                (Nbt::LongArray(values), Nbt::Long(v)) => values.push(v),
+                (array, value) => {
+                    self.pos = start;
+                    return Err(self.error(
+                        "argument.nbt.array.mixed",
+                        vec![Message::text(value.kind()), Message::text(array.kind())],
+                    ));
+                }
+            }
+            if !self.separator(']')? {
+                break;
+            }
+        }
+        self.expect(']')?;
+        Ok(array)
+    }
+}
+
+/// Reads one SNBT value from the start of the input and returns the rest. Errors are [`Located`]
+/// relative to the start of the input.
+pub(crate) fn snbt(input: &str) -> Result<(Nbt, &str)> {
+    let mut reader = Reader { input, pos: 0 };
+    let value = reader.value(0)?;
+    Ok((value, reader.rest()))
+}
+
+/// Reads an SNBT compound, which is what most commands accept.
+pub(crate) fn snbt_compound(input: &str) -> Result<(Nbt, &str)> {
+    let mut reader = Reader { input, pos: 0 };
+    let value = reader.compound(0)?;
+    Ok((value, reader.rest()))
+}
+
+impl FromStr for Nbt {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut reader = Reader { input: s, pos: 0 };
+        let value = reader.value(0)?;
+        reader.skip_whitespace();
+        if !reader.rest().is_empty() {
+            return Err(reader.error("argument.nbt.trailing", vec![]));
+        }
+        Ok(value)
+    }
+}
+
+#[derive(Default)]
+pub struct SnbtParser;
+
+impl IterParser for SnbtParser {
+    type Extract = (Nbt,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        (snbt(input).map(|(nbt, out)| ((nbt,), out)), None)
+    }
+
+    fn regex(&self) -> String {
+        // Nesting can't be described by a regex, so anything that starts and ends like a compound or list is let through.
+        String::from(
+            "(\\{.*\\}|\\[.*\\]|\"([^\"\\\\]|\\\\.)*\"|'([^'\\\\]|\\\\.)*'|[0-9A-Za-z_\\.\\+\\-]+)",
+        )
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument("nbt", ArgumentType::NbtTag)]]
+    }
+}
+
+impl Argument for Nbt {
+    type Parser = SnbtParser;
+    type ParserState = ();
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::feedback::Message;
+    use crate::parser::{IterParser, Located};
+    use crate::regex::{DFA, NFA};
+
+    use super::{snbt, Nbt, SnbtParser};
+
+    fn parse(input: &str) -> Nbt {
+        let (nbt, out) = snbt(input).unwrap();
+        assert_eq!(out, "");
+        nbt
+    }
+
+    fn error(input: &str) -> (usize, Message) {
+        let (position, error) = Located::split(snbt(input).unwrap_err());
+        (position, Message::from(&error))
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(parse("1b"), Nbt::Byte(1));
+        assert_eq!(parse("-3S"), Nbt::Short(-3));
+        assert_eq!(parse("10"), Nbt::Int(10));
+        assert_eq!(parse("10L"), Nbt::Long(10));
+        assert_eq!(parse("2.5f"), Nbt::Float(2.5));
+        assert_eq!(parse("2.5"), Nbt::Double(2.5));
+        assert_eq!(parse("1e3d"), Nbt::Double(1000.0));
+        assert_eq!(parse("true"), Nbt::Byte(1));
+        assert_eq!(parse("300b"), Nbt::String(String::from("300b")));
+        assert_eq!(parse("01"), Nbt::String(String::from("01")));
+        assert_eq!(parse("'a \\'b\\''"), Nbt::String(String::from("a 'b'")));
+        assert_eq!(parse("[B; 1b, 2b]"), Nbt::ByteArray(vec![1, 2]));
+        assert_eq!(parse("[I;]"), Nbt::IntArray(vec![]));
+        assert_eq!(parse("[L;1L]"), Nbt::LongArray(vec![1]));
+    }
+
+    #[test]
+    fn nested() {
+        let nbt = parse("{Enchantments:[{id:\"minecraft:sharpness\",lvl:5s}], \"a b\": {}}");
+        let mut enchantment = IndexMap::new();
+        enchantment.insert(
+            String::from("id"),
+            Nbt::String(String::from("minecraft:sharpness")),
+        );
+        enchantment.insert(String::from("lvl"), Nbt::Short(5));
+        let mut expected = IndexMap::new();
+        expected.insert(
+            String::from("Enchantments"),
+            Nbt::List(vec![Nbt::Compound(enchantment)]),
+        );
+        expected.insert(String::from("a b"), Nbt::Compound(IndexMap::new()));
+        assert_eq!(nbt, Nbt::Compound(expected));
+
+        assert_eq!(parse(&nbt.to_string()), nbt);
+
+        let ((_,), out) = SnbtParser.parse((), "{a:[1,2]} 5").0.unwrap();
+        assert_eq!(out, " 5");
+    }
+
+    #[test]
+    fn errors() {
+        let translatable = |key, with: &[&str]| {
+            Message::translatable(key, with.iter().map(|arg| Message::text(*arg)).collect())
+        };
+        assert_eq!(
+            error("[1, 2b]"),
+            (4, translatable("argument.nbt.list.mixed", &["byte", "int"]))
+        );
+        assert_eq!(
+            error("[I; 1, 2L]"),
+            (
+                7,
+                translatable("argument.nbt.array.mixed", &["long", "int array"])
+            )
+        );
+        assert_eq!(
+            error("[X; 1]"),
+            (1, translatable("argument.nbt.array.invalid", &["X"]))
+        );
+        assert_eq!(error("{a:1"), (4, translatable("parsing.expected", &["}"])));
+        assert_eq!(
+            error("{a 1}"),
+            (3, translatable("parsing.expected", &[":"]))
+        );
+        assert_eq!(
+            error("{:1}"),
+            (1, translatable("argument.nbt.expected.key", &[]))
+        );
+        assert_eq!(
+            error("\"abc"),
+            (0, translatable("parsing.quote.expected.end", &[]))
+        );
+        assert_eq!(
+            error("[1,]"),
+            (3, translatable("argument.nbt.expected.value", &[]))
+        );
+        assert_eq!(
+            error(&"[".repeat(600)).1,
+            translatable("lieutenant.nbt.depth", &["512"])
+        );
+
+        assert_eq!("{a:1} ".parse::<Nbt>().unwrap(), parse("{a:1}"));
+        let trailing = "{a:1} b".parse::<Nbt>().unwrap_err();
+        assert_eq!(Located::split(trailing).0, 6);
+    }
+
+    #[test]
+    fn regex() {
+        let dfa = DFA::<usize>::from(NFA::<usize>::regex(&SnbtParser.regex()).unwrap());
+        for input in &["{a:[1,2]}", "[I;1]", "\"a b\"", "1.5f"] {
+            assert!(dfa.find(input).is_ok(), "{}", input);
+        }
+    }
+}
@@ -1,7 +1,12 @@
+use anyhow::anyhow;
+
+use crate::brigadier::{ArgumentType, NodeKind};
+use crate::feedback::Message;
 use crate::parser::IterParser;
 
 use super::Argument;
 
+#[derive(Default)]
 pub struct U32Parser {}
 
 impl IterParser for U32Parser {
@@ -19,38 +24,49 @@ impl IterParser for U32Parser {
         Option<Self::ParserState>,
     ) {
         // Consume digit from head of input
-
-        let mut iter = input.char_indices();
-        let mut index = 0;
-        if let Some((i, c)) = iter.next() {
-            if c == '+' || c == '-' {
-                index = i;
-            }
+        let digits_start = if input.starts_with('+') || input.starts_with('-') {
+            1
         } else {
-            return (Err(anyhow::anyhow!("Empty input")), None);
-        }
+            0
+        };
+        let end = input[digits_start..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(input.len(), |i| i + digits_start);
 
-        for (i, c) in iter {
-            if !c.is_digit(10) {
-                break;
-            }
-            index = i
+        let digits = &input[digits_start..end];
+        if digits.is_empty() {
+            let error = Message::translatable("parsing.int.expected", vec![]);
+            return (Err(anyhow!(error)), None);
         }
 
-        match input[0..=index].parse::<u32>() {
-            Ok(number) => (Ok(((number,), input)), None),
-            Err(_) => (Err(anyhow::anyhow!("Not a number")), None),
-        }
+        let negative = input.starts_with('-');
+        let bound = match digits.parse::<u32>() {
+            Ok(number) if !negative => return (Ok(((number,), &input[end..])), None),
+            _ if negative => ("argument.integer.low", 0),
+            _ => ("argument.integer.big", u32::MAX),
+        };
+        let error = Message::translatable(
+            bound.0,
+            vec![
+                Message::text(bound.1.to_string()),
+                Message::text(&input[..end]),
+            ],
+        );
+        (Err(anyhow!(error)), None)
     }
 
     fn regex(&self) -> String {
         "[\\+|-]?\\d+".into()
     }
-}
 
-impl Default for U32Parser {
-    fn default() -> Self {
-        Self {}
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument(
+            "number",
+            ArgumentType::Long {
+                min: Some(0),
+                max: Some(u32::MAX.into()),
+            },
+        )]]
     }
 }
 
@@ -0,0 +1,214 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::brigadier::{ArgumentType, NodeKind};
+use crate::feedback::Message;
+use crate::parser::IterParser;
+
+use super::Argument;
+
+/// A range of integers like `5`, `..5`, `1..` or `2..8`, where both bounds are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct IntRange {
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+}
+
+impl IntRange {
+    pub fn contains(&self, value: i32) -> bool {
+        self.min.is_none_or(|min| min <= value) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+/// A range of numbers like `5`, `..5.5`, `1..` or `2..8`, where both bounds are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FloatRange {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl FloatRange {
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| min <= value) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+/// Returns the length of the number at the start of the input. A dot only belongs to the number
+/// if it is not the start of `..`.
+fn number_len(input: &str, decimal: bool) -> usize {
+    let bytes = input.as_bytes();
+    let mut end = 0;
+    while end < bytes.len() {
+        match bytes[end] {
+            b'0'..=b'9' | b'-' => end += 1,
+            b'.' if decimal && bytes.get(end + 1) != Some(&b'.') => end += 1,
+            _ => break,
+        }
+    }
+    end
+}
+
+fn bound<T: FromStr>(input: &str, decimal: bool) -> Result<(Option<T>, &str)> {
+    let end = number_len(input, decimal);
+    if end == 0 {
+        return Ok((None, input));
+    }
+    let value = input[..end].parse().map_err(|_| {
+        let key = if decimal {
+            "parsing.float.invalid"
+        } else {
+            "parsing.int.invalid"
+        };
+        anyhow!(Message::translatable(
+            key,
+            vec![Message::text(&input[..end])]
+        ))
+    })?;
+    Ok((Some(value), &input[end..]))
+}
+
+/// Reads a range from the start of the input and returns the rest.
+#[allow(clippy::type_complexity)]
+fn range<T: FromStr + PartialOrd + Copy>(
+    input: &str,
+    decimal: bool,
+) -> Result<((Option<T>, Option<T>), &str)> {
+    let (min, out) = bound::<T>(input, decimal)?;
+    let (max, out) = match out.strip_prefix("..") {
+        Some(out) => bound::<T>(out, decimal)?,
+        None => (min, out),
+    };
+
+    match (min, max) {
+        (None, None) => bail!(Message::translatable("argument.range.empty", vec![])),
+        (Some(min), Some(max)) if min > max => {
+            bail!(Message::translatable("argument.range.swapped", vec![]))
+        }
+
+        range => Ok((range, out)),
+    }
+}
+
+/// Reads an [`IntRange`] from the start of the input and returns the rest, which is how selector filters embed it.
+pub(crate) fn int_range(input: &str) -> Result<(IntRange, &str)> {
+    range(input, false).map(|((min, max), out)| (IntRange { min, max }, out))
+}
+
+/// Reads a [`FloatRange`] from the start of the input and returns the rest, which is how selector filters embed it.
+pub(crate) fn float_range(input: &str) -> Result<(FloatRange, &str)> {
+    range(input, true).map(|((min, max), out)| (FloatRange { min, max }, out))
+}
+
+#[derive(Default)]
+pub struct IntRangeParser;
+
+impl IterParser for IntRangeParser {
+    type Extract = (IntRange,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        (int_range(input).map(|(range, out)| ((range,), out)), None)
+    }
+
+    fn regex(&self) -> String {
+        String::from("(-?\\d+(\\.\\.(-?\\d+)?)?|\\.\\.-?\\d+)")
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument("range", ArgumentType::IntRange)]]
+    }
+}
+
+impl Argument for IntRange {
+    type Parser = IntRangeParser;
+    type ParserState = ();
+}
+
+#[derive(Default)]
+pub struct FloatRangeParser;
+
+impl IterParser for FloatRangeParser {
+    type Extract = (FloatRange,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        (float_range(input).map(|(range, out)| ((range,), out)), None)
+    }
+
+    fn regex(&self) -> String {
+        let number = "-?(\\d+(\\.\\d*)?|\\.\\d+)";
+        format!("({0}(\\.\\.({0})?)?|\\.\\.{0})", number)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument("range", ArgumentType::FloatRange)]]
+    }
+}
+
+impl Argument for FloatRange {
+    type Parser = FloatRangeParser;
+    type ParserState = ();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::IterParser;
+    use crate::regex::{DFA, NFA};
+
+    use super::{float_range, int_range, FloatRange, FloatRangeParser, IntRange, IntRangeParser};
+
+    #[test]
+    fn int() {
+        let range = |min, max| IntRange { min, max };
+        assert_eq!(int_range("5").unwrap(), (range(Some(5), Some(5)), ""));
+        assert_eq!(int_range("..5").unwrap(), (range(None, Some(5)), ""));
+        assert_eq!(int_range("-1.. x").unwrap(), (range(Some(-1), None), " x"));
+        assert_eq!(int_range("2..8").unwrap(), (range(Some(2), Some(8)), ""));
+
+        let (range, _) = int_range("2..8").unwrap();
+        assert!(range.contains(2) && range.contains(8));
+        assert!(!range.contains(1) && !range.contains(9));
+
+        for input in &["..", "", "8..2", "1.5", "a"] {
+            let result = IntRangeParser.parse((), input).0;
+            assert!(!matches!(result, Ok((_, ""))), "{}", input);
+        }
+        let error = int_range("8..2").unwrap_err().to_string();
+        assert_eq!(error, "Min cannot be bigger than max");
+    }
+
+    #[test]
+    fn float() {
+        let range = |min, max| FloatRange { min, max };
+        assert_eq!(float_range("1.5..").unwrap(), (range(Some(1.5), None), ""));
+        assert_eq!(float_range("..-.5").unwrap(), (range(None, Some(-0.5)), ""));
+        assert_eq!(
+            float_range("1..2.").unwrap(),
+            (range(Some(1.0), Some(2.0)), "")
+        );
+        assert!(float_range("2.5..1").is_err());
+        assert!(float_range("1..2").unwrap().0.contains(1.5));
+    }
+
+    #[test]
+    fn regex() {
+        let int = DFA::<usize>::from(NFA::<usize>::regex(&IntRangeParser.regex()).unwrap());
+        let float = DFA::<usize>::from(NFA::<usize>::regex(&FloatRangeParser.regex()).unwrap());
+        for input in &["5", "..5", "1..", "-2..8"] {
+            assert!(int.find(input).is_ok(), "{}", input);
+            assert!(float.find(input).is_ok(), "{}", input);
+        }
+        assert!(float.find("..-.5").is_ok());
+    }
+}
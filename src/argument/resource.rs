@@ -0,0 +1,389 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::brigadier::{ArgumentType, NodeKind};
+use crate::feedback::Message;
+use crate::parser::{IterParser, Separator, Suggestion};
+
+use super::{Argument, BlockRegistry, ItemRegistry};
+
+/// A namespaced id like `minecraft:stone`. The namespace defaults to `minecraft` when it is left out.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ResourceLocation {
+    pub namespace: String,
+    pub path: String,
+}
+
+impl ResourceLocation {
+    pub const DEFAULT_NAMESPACE: &'static str = "minecraft";
+
+    pub fn new(namespace: impl Into<String>, path: impl Into<String>) -> Result<Self> {
+        let (namespace, path) = (namespace.into(), path.into());
+        if !namespace.chars().all(namespace_char) || path.is_empty() || !path.chars().all(path_char)
+        {
+            bail!(Message::translatable("argument.id.invalid", vec![]));
+        }
+        Ok(Self { namespace, path })
+    }
+
+    /// A location in the `minecraft` namespace.
+    pub fn minecraft(path: impl Into<String>) -> Result<Self> {
+        Self::new(Self::DEFAULT_NAMESPACE, path)
+    }
+}
+
+impl fmt::Display for ResourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+impl FromStr for ResourceLocation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match resource_location(s)? {
+            (location, "") => Ok(location),
+            (_, _) => Err(anyhow!(Message::translatable(
+                "argument.id.invalid",
+                vec![]
+            ))),
+        }
+    }
+}
+
+fn namespace_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.' || c == '-'
+}
+
+fn path_char(c: char) -> bool {
+    namespace_char(c) || c == '/'
+}
+
+/// Reads a resource location from the start of the input and returns the rest, which is how other
+/// arguments like block states embed it.
+pub(crate) fn resource_location(input: &str) -> Result<(ResourceLocation, &str)> {
+    let end = input
+        .find(|c| !path_char(c) && c != ':')
+        .unwrap_or(input.len());
+    let (id, out) = input.split_at(end);
+    if id.is_empty() {
+        bail!(Message::translatable("argument.id.invalid", vec![]));
+    }
+
+    let location = match id.find(':') {
+        // Like vanilla, `:stone` is in the default namespace too.
+        Some(0) => ResourceLocation::minecraft(&id[1..])?,
+        Some(colon) => ResourceLocation::new(&id[..colon], &id[colon + 1..])?,
+        None => ResourceLocation::minecraft(id)?,
+    };
+    Ok((location, out))
+}
+
+/// The ids a [`ResourceLocationParser`] accepts, like the blocks or items of the game.
+pub trait Registry {
+    fn contains(&self, location: &ResourceLocation) -> bool;
+
+    /// Every id in the registry, used for suggestions.
+    fn locations(&self) -> Vec<ResourceLocation>;
+}
+
+/// The registries of the game, which parsers use to reject unknown ids and suggest known ones. The
+/// dispatcher gets them from the game state, see [`GameStateFamily::registries`](crate::command::GameStateFamily::registries).
+/// Registries the game leaves out are not checked.
+pub trait Registries {
+    /// The registry with the key `key`, like `minecraft:block`, see [`ResourceLocationParser::registry`].
+    fn registry(&self, _key: &ResourceLocation) -> Option<&dyn Registry> {
+        None
+    }
+
+    fn blocks(&self) -> Option<&dyn BlockRegistry> {
+        None
+    }
+
+    fn items(&self) -> Option<&dyn ItemRegistry> {
+        None
+    }
+}
+
+/// No registries, so any well formed id is accepted.
+impl Registries for () {}
+
+/// Parses a [`ResourceLocation`]. Any well formed id is accepted, unless the parser is made with
+/// [`ResourceLocationParser::registry`] and the [`Registries`] it is given through [`IterParser::parse_in`]
+/// have that registry.
+#[derive(Default)]
+pub struct ResourceLocationParser {
+    separator: Separator,
+    registry: Option<ResourceLocation>,
+}
+
+impl ResourceLocationParser {
+    /// A parser for the ids in the registry with the key `key`, like `minecraft:block`.
+    pub fn registry(key: ResourceLocation) -> Self {
+        Self {
+            registry: Some(key),
+            ..Self::default()
+        }
+    }
+
+    fn lookup<'r>(&self, registries: &'r dyn Registries) -> Option<&'r dyn Registry> {
+        self.registry
+            .as_ref()
+            .and_then(|key| registries.registry(key))
+    }
+}
+
+impl IterParser for ResourceLocationParser {
+    type Extract = (ResourceLocation,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        self.parse_in(&(), state, input)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn parse_in<'p>(
+        &self,
+        registries: &dyn Registries,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        let result =
+            resource_location(input).and_then(|(location, out)| match self.lookup(registries) {
+                Some(registry) if !registry.contains(&location) => {
+                    Err(anyhow!(Message::translatable(
+                        "argument.id.unknown",
+                        vec![Message::text(location.to_string())]
+                    )))
+                }
+                _ => Ok(((location,), out)),
+            });
+        (result, None)
+    }
+
+    fn regex(&self) -> String {
+        String::from("([a-z0-9_\\.\\-]*:)?[a-z0-9_\\./\\-]+")
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument(
+            "id",
+            ArgumentType::ResourceLocation,
+        )]]
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        self.suggest_in(&(), input)
+    }
+
+    fn suggest_in(&self, registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        let registry = match self.lookup(registries) {
+            Some(registry) => registry,
+            None => return Vec::new(),
+        };
+        if input.contains(|c| self.separator.is_separator(c)) {
+            return Vec::new();
+        }
+
+        let mut suggestions = Vec::new();
+        for location in registry.locations() {
+            let full = location.to_string();
+            if full.starts_with(input) {
+                suggestions.push(Suggestion {
+                    start: 0,
+                    text: full,
+                });
+            } else if location.namespace == ResourceLocation::DEFAULT_NAMESPACE
+                && location.path.starts_with(input)
+            {
+                suggestions.push(Suggestion {
+                    start: 0,
+                    text: location.path,
+                });
+            }
+        }
+        suggestions
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.separator = separator.clone();
+    }
+}
+
+impl Argument for ResourceLocation {
+    type Parser = ResourceLocationParser;
+    type ParserState = ();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::{
+        builder::{literal, CommandBuilder},
+        GameStateFamily,
+    };
+    use crate::dispatcher::Dispatcher;
+    use crate::parser::{IterParser, Separator};
+    use crate::source::{CommandSource, Sourced};
+
+    use super::{Registries, Registry, ResourceLocation, ResourceLocationParser};
+
+    fn blocks() -> ResourceLocationParser {
+        ResourceLocationParser::registry(ResourceLocation::minecraft("block").unwrap())
+    }
+
+    struct Blocks;
+
+    impl Registry for Blocks {
+        fn contains(&self, location: &ResourceLocation) -> bool {
+            self.locations().contains(location)
+        }
+
+        fn locations(&self) -> Vec<ResourceLocation> {
+            vec![
+                ResourceLocation::minecraft("stone").unwrap(),
+                ResourceLocation::minecraft("stone_bricks").unwrap(),
+                ResourceLocation::new("mymod", "stone/blue").unwrap(),
+            ]
+        }
+    }
+
+    struct Game {
+        blocks: Blocks,
+    }
+
+    impl Registries for Game {
+        fn registry(&self, key: &ResourceLocation) -> Option<&dyn Registry> {
+            match key.to_string().as_str() {
+                "minecraft:block" => Some(&self.blocks),
+                _ => None,
+            }
+        }
+    }
+
+    struct GameFamily;
+
+    impl GameStateFamily for GameFamily {
+        type GameState<'a> = (&'a Game,);
+
+        fn reborrow<'b>(gamestate: &'b mut Self::GameState<'_>) -> Self::GameState<'b> {
+            (gamestate.0,)
+        }
+
+        fn registries<'b>(gamestate: &'b Self::GameState<'_>) -> &'b dyn Registries {
+            gamestate.0
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let parser = ResourceLocationParser::default();
+        let ((location,), out) = parser.parse((), "stone rest").0.unwrap();
+        assert_eq!(location, ResourceLocation::minecraft("stone").unwrap());
+        assert_eq!(out, " rest");
+
+        let ((location,), _) = parser.parse((), "my_mod:blocks/blue.stone").0.unwrap();
+        assert_eq!(location.to_string(), "my_mod:blocks/blue.stone");
+
+        for input in &["", "Stone", "my/mod:stone", "mymod:", "a:b:c"] {
+            assert!(input.parse::<ResourceLocation>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn empty_namespace() {
+        let location = ":stone".parse::<ResourceLocation>().unwrap();
+        assert_eq!(location, ResourceLocation::minecraft("stone").unwrap());
+        assert!(":".parse::<ResourceLocation>().is_err());
+    }
+
+    #[test]
+    fn registry() {
+        let game = Game { blocks: Blocks };
+        let parser = blocks();
+        assert!(parser.parse_in(&game, (), "minecraft:stone").0.is_ok());
+        assert!(parser.parse_in(&game, (), "dirt").0.is_err());
+        assert!(parser.parse((), "dirt").0.is_ok());
+
+        let items = ResourceLocationParser::registry(ResourceLocation::minecraft("item").unwrap());
+        assert!(items.parse_in(&game, (), "dirt").0.is_ok());
+        assert!(items.suggest_in(&game, "stone").is_empty());
+        let any = ResourceLocationParser::default();
+        assert!(any.parse_in(&game, (), "dirt").0.is_ok());
+
+        let texts = |input| {
+            parser
+                .suggest_in(&game, input)
+                .into_iter()
+                .map(|s| s.text)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts("stone"), vec!["stone", "stone_bricks"]);
+        assert_eq!(texts("minecraft:stone_"), vec!["minecraft:stone_bricks"]);
+        assert_eq!(texts("mymod"), vec!["mymod:stone/blue"]);
+        assert!(texts("stone ").is_empty());
+        assert!(parser.suggest("stone").is_empty());
+    }
+
+    #[test]
+    fn separator() {
+        let game = Game { blocks: Blocks };
+        let mut parser = blocks();
+        parser.set_separator(&Separator::chars([',']).unwrap());
+        assert_eq!(parser.parse((), "stone,rest").0.unwrap().1, ",rest");
+        assert!(parser.suggest_in(&game, "stone,").is_empty());
+        assert_eq!(parser.suggest_in(&game, "stone_").len(), 1);
+    }
+
+    #[test]
+    fn game_state() {
+        let mut dispatcher = Dispatcher::<GameFamily, String>::new();
+        dispatcher
+            .register(
+                literal("/setblock")
+                    .space()
+                    .followed_by(blocks())
+                    .on_call(|block: ResourceLocation| move |_: &Game| block.to_string()),
+            )
+            .unwrap();
+
+        let game = Game { blocks: Blocks };
+        assert_eq!(
+            dispatcher.call((&game,), "/setblock :stone").unwrap(),
+            "minecraft:stone"
+        );
+        assert!(dispatcher.call((&game,), "/setblock dirt").is_err());
+
+        let texts = dispatcher
+            .suggest_with(&(&game,), "/setblock mymod:")
+            .into_iter()
+            .map(|s| s.text)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["mymod:stone/blue"]);
+    }
+
+    #[test]
+    fn sourced() {
+        let mut dispatcher = Dispatcher::<Sourced<Game>, String>::new();
+        dispatcher
+            .register(literal("/setblock").space().followed_by(blocks()).on_call(
+                |block: ResourceLocation| move |_: &mut Game, _: &CommandSource| block.to_string(),
+            ))
+            .unwrap();
+
+        let mut game = Game { blocks: Blocks };
+        let source = CommandSource::console();
+        assert!(dispatcher
+            .call((&mut game, &source), "/setblock stone")
+            .is_ok());
+        assert!(dispatcher
+            .call((&mut game, &source), "/setblock dirt")
+            .is_err());
+    }
+}
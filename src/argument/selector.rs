@@ -0,0 +1,821 @@
+use anyhow::{anyhow, bail, Result};
+
+use crate::brigadier::{ArgumentType, NodeKind};
+use crate::feedback::Message;
+use crate::parser::{IterParser, Separator, Suggestion};
+
+use super::{
+    nbt::snbt_compound,
+    range::{float_range, int_range},
+    Argument, FloatRange, IntRange, Nbt,
+};
+
+/// The target of a command, like `Notch`, `@p` or `@e[type=zombie,limit=3]`. The game evaluates it
+/// against the entities in the world.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntitySelector {
+    Player(String),
+    Uuid(u128),
+    Selector {
+        kind: SelectorKind,
+        filters: Vec<SelectorFilter>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorKind {
+    /// `@p`
+    NearestPlayer,
+    /// `@a`
+    AllPlayers,
+    /// `@r`
+    RandomPlayer,
+    /// `@s`
+    Executor,
+    /// `@e`
+    AllEntities,
+}
+
+impl EntitySelector {
+    /// If the selector is `@s`, which selects the entity the command runs as, if it passes the filters.
+    pub fn is_executor(&self) -> bool {
+        matches!(
+            self,
+            EntitySelector::Selector {
+                kind: SelectorKind::Executor,
+                ..
+            }
+        )
+    }
+
+    /// The most entities the selector can select, or `None` if it has no limit.
+    fn max_results(&self) -> Option<u32> {
+        let (kind, filters) = match self {
+            EntitySelector::Player(_) | EntitySelector::Uuid(_) => return Some(1),
+            EntitySelector::Selector { kind, filters } => (kind, filters),
+        };
+        let limit = filters.iter().find_map(|filter| match filter {
+            SelectorFilter::Limit(limit) => Some(*limit),
+            _ => None,
+        });
+        match kind {
+            SelectorKind::Executor => Some(1),
+            SelectorKind::NearestPlayer | SelectorKind::RandomPlayer => Some(limit.unwrap_or(1)),
+            SelectorKind::AllPlayers | SelectorKind::AllEntities => limit,
+        }
+    }
+
+    /// If the selector can select entities other than players. Like in Minecraft, `@s` does not count,
+    /// because the executor is only known when the command runs.
+    fn includes_entities(&self) -> bool {
+        match self {
+            EntitySelector::Player(_) => false,
+            EntitySelector::Uuid(_) => true,
+            EntitySelector::Selector {
+                kind: SelectorKind::AllEntities,
+                filters,
+            } => !filters.iter().any(|filter| {
+                matches!(filter, SelectorFilter::Type(ty)
+                    if !ty.negated && (ty.value == "player" || ty.value == "minecraft:player"))
+            }),
+            EntitySelector::Selector { .. } => false,
+        }
+    }
+}
+
+impl SelectorKind {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'p' => Some(SelectorKind::NearestPlayer),
+            'a' => Some(SelectorKind::AllPlayers),
+            'r' => Some(SelectorKind::RandomPlayer),
+            's' => Some(SelectorKind::Executor),
+            'e' => Some(SelectorKind::AllEntities),
+            _ => None,
+        }
+    }
+
+    fn only_players(&self) -> bool {
+        matches!(
+            self,
+            SelectorKind::NearestPlayer | SelectorKind::AllPlayers | SelectorKind::RandomPlayer
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    Nearest,
+    Furthest,
+    Random,
+    Arbitrary,
+}
+
+/// A filter value that can be inverted with `!`, like `tag=!foo`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Negatable<T> {
+    pub negated: bool,
+    pub value: T,
+}
+
+/// One `key=value` pair between the brackets of a selector. Scores and advancements are kept as
+/// they were written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectorFilter {
+    X(f64),
+    Y(f64),
+    Z(f64),
+    Dx(f64),
+    Dy(f64),
+    Dz(f64),
+    Distance(FloatRange),
+    XRotation(FloatRange),
+    YRotation(FloatRange),
+    Level(IntRange),
+    Limit(u32),
+    Sort(Sort),
+    Gamemode(Negatable<String>),
+    Name(Negatable<String>),
+    Type(Negatable<String>),
+    Tag(Negatable<String>),
+    Team(Negatable<String>),
+    Predicate(Negatable<String>),
+    Nbt(Negatable<Nbt>),
+    Scores(String),
+    Advancements(String),
+}
+
+const KEYS: &[&str] = &[
+    "x",
+    "y",
+    "z",
+    "dx",
+    "dy",
+    "dz",
+    "distance",
+    "x_rotation",
+    "y_rotation",
+    "level",
+    "limit",
+    "sort",
+    "gamemode",
+    "name",
+    "type",
+    "tag",
+    "team",
+    "predicate",
+    "nbt",
+    "scores",
+    "advancements",
+];
+
+const SORTS: &[&str] = &["nearest", "furthest", "random", "arbitrary"];
+
+const GAMEMODES: &[&str] = &["survival", "creative", "adventure", "spectator"];
+
+fn take_while(input: &str, predicate: impl Fn(char) -> bool) -> (&str, &str) {
+    let end = input.find(|c| !predicate(c)).unwrap_or(input.len());
+    input.split_at(end)
+}
+
+fn unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '+'
+}
+
+fn resource_char(c: char) -> bool {
+    unquoted_char(c) || c == ':' || c == '/' || c == '#'
+}
+
+/// Reads a string that is either quoted with `"` or `'`, or made of unquoted characters.
+fn string(input: &str) -> Result<(String, &str)> {
+    let quote = match input.chars().next() {
+        Some(c @ '"') | Some(c @ '\'') => c,
+        _ => {
+            let (value, out) = take_while(input, unquoted_char);
+            return Ok((String::from(value), out));
+        }
+    };
+
+    let mut value = String::new();
+    let mut escaped = false;
+    for (i, c) in input.char_indices().skip(1) {
+        match c {
+            _ if escaped => {
+                value.push(c);
+                escaped = false;
+            }
+            '\\' => escaped = true,
+            c if c == quote => return Ok((value, &input[i + 1..])),
+            c => value.push(c),
+        }
+    }
+    bail!(Message::translatable("parsing.quote.expected.end", vec![]))
+}
+
+/// Reads a `{...}` value up to the matching brace, keeping it as written.
+fn balanced(input: &str) -> Result<(String, &str)> {
+    if !input.starts_with('{') {
+        bail!(Message::translatable(
+            "parsing.expected",
+            vec![Message::text("{")]
+        ));
+    }
+
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in input.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '{') | (None, '[') => depth += 1,
+            (None, '}') | (None, ']') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((String::from(&input[..=i]), &input[i + 1..]));
+                }
+            }
+            _ => {}
+        }
+    }
+    bail!(Message::translatable(
+        "parsing.expected",
+        vec![Message::text("}")]
+    ))
+}
+
+fn negatable<'a, T>(
+    input: &'a str,
+    value: impl Fn(&'a str) -> Result<(T, &'a str)>,
+) -> Result<(Negatable<T>, &'a str)> {
+    let (negated, input) = match input.strip_prefix('!') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, input),
+    };
+    let (value, out) = value(input)?;
+    Ok((Negatable { negated, value }, out))
+}
+
+fn float(input: &str) -> Result<(f64, &str)> {
+    let (value, out) = take_while(input, unquoted_char);
+    let value = value.parse().map_err(|_| {
+        anyhow!(Message::translatable(
+            "parsing.double.invalid",
+            vec![Message::text(value)]
+        ))
+    })?;
+    Ok((value, out))
+}
+
+fn filter<'a>(key: &str, input: &'a str) -> Result<(SelectorFilter, &'a str)> {
+    let resource = |input: &'a str| {
+        let (value, out) = take_while(input, resource_char);
+        if value.is_empty() {
+            bail!(Message::translatable("argument.id.invalid", vec![]));
+        }
+        Ok((String::from(value), out))
+    };
+
+    Ok(match key {
+        "x" => float(input).map(|(v, out)| (SelectorFilter::X(v), out))?,
+        "y" => float(input).map(|(v, out)| (SelectorFilter::Y(v), out))?,
+        "z" => float(input).map(|(v, out)| (SelectorFilter::Z(v), out))?,
+        "dx" => float(input).map(|(v, out)| (SelectorFilter::Dx(v), out))?,
+        "dy" => float(input).map(|(v, out)| (SelectorFilter::Dy(v), out))?,
+        "dz" => float(input).map(|(v, out)| (SelectorFilter::Dz(v), out))?,
+        "distance" => {
+            let (range, out) = float_range(input)?;
+            if range.min.is_some_and(|min| min < 0.0) || range.max.is_some_and(|max| max < 0.0) {
+                bail!(Message::translatable(
+                    "argument.entity.options.distance.negative",
+                    vec![]
+                ));
+            }
+            (SelectorFilter::Distance(range), out)
+        }
+        "x_rotation" => float_range(input).map(|(v, out)| (SelectorFilter::XRotation(v), out))?,
+        "y_rotation" => float_range(input).map(|(v, out)| (SelectorFilter::YRotation(v), out))?,
+        "level" => {
+            let (range, out) = int_range(input)?;
+            if range.min.is_some_and(|min| min < 0) || range.max.is_some_and(|max| max < 0) {
+                bail!(Message::translatable(
+                    "argument.entity.options.level.negative",
+                    vec![]
+                ));
+            }
+            (SelectorFilter::Level(range), out)
+        }
+        "limit" => {
+            let (value, out) = take_while(input, |c| c.is_ascii_digit());
+            match value.parse() {
+                Ok(limit) if limit > 0 => (SelectorFilter::Limit(limit), out),
+                _ => bail!(Message::translatable(
+                    "argument.entity.options.limit.toosmall",
+                    vec![]
+                )),
+            }
+        }
+        "sort" => {
+            let (value, out) = take_while(input, unquoted_char);
+            let sort = match value {
+                "nearest" => Sort::Nearest,
+                "furthest" => Sort::Furthest,
+                "random" => Sort::Random,
+                "arbitrary" => Sort::Arbitrary,
+                _ => bail!(Message::translatable(
+                    "argument.entity.options.sort.irreversible",
+                    vec![Message::text(value)]
+                )),
+            };
+            (SelectorFilter::Sort(sort), out)
+        }
+        "gamemode" => {
+            let (gamemode, out) = negatable(input, |input| Ok(take_while(input, unquoted_char)))?;
+            if !GAMEMODES.contains(&gamemode.value) {
+                bail!(Message::translatable(
+                    "argument.entity.options.mode.invalid",
+                    vec![Message::text(gamemode.value)]
+                ));
+            }
+            let gamemode = Negatable {
+                negated: gamemode.negated,
+                value: String::from(gamemode.value),
+            };
+            (SelectorFilter::Gamemode(gamemode), out)
+        }
+        "name" => negatable(input, string).map(|(v, out)| (SelectorFilter::Name(v), out))?,
+        "type" => negatable(input, resource).map(|(v, out)| (SelectorFilter::Type(v), out))?,
+        "tag" => negatable(input, string).map(|(v, out)| (SelectorFilter::Tag(v), out))?,
+        "team" => negatable(input, string).map(|(v, out)| (SelectorFilter::Team(v), out))?,
+        "predicate" => {
+            negatable(input, resource).map(|(v, out)| (SelectorFilter::Predicate(v), out))?
+        }
+        "nbt" => negatable(input, snbt_compound).map(|(v, out)| (SelectorFilter::Nbt(v), out))?,
+        "scores" => balanced(input).map(|(v, out)| (SelectorFilter::Scores(v), out))?,
+        "advancements" => balanced(input).map(|(v, out)| (SelectorFilter::Advancements(v), out))?,
+        _ => bail!(Message::translatable(
+            "argument.entity.options.unknown",
+            vec![Message::text(key)]
+        )),
+    })
+}
+
+/// Checks the rules Minecraft has for combining filters: most of them may only be given once,
+/// and some do not make sense for every kind of selector.
+fn applicable(kind: SelectorKind, filters: &[SelectorFilter], filter: &SelectorFilter) -> bool {
+    let same =
+        |other: &&SelectorFilter| std::mem::discriminant(*other) == std::mem::discriminant(filter);
+    let positive = |other: &&SelectorFilter| match other {
+        SelectorFilter::Gamemode(v)
+        | SelectorFilter::Name(v)
+        | SelectorFilter::Type(v)
+        | SelectorFilter::Team(v) => !v.negated,
+        _ => true,
+    };
+
+    match filter {
+        SelectorFilter::Limit(_) | SelectorFilter::Sort(_) if kind == SelectorKind::Executor => {
+            false
+        }
+        SelectorFilter::Type(_) if kind.only_players() => false,
+        SelectorFilter::Tag(_) | SelectorFilter::Predicate(_) | SelectorFilter::Nbt(_) => true,
+        SelectorFilter::Gamemode(v)
+        | SelectorFilter::Name(v)
+        | SelectorFilter::Type(v)
+        | SelectorFilter::Team(v) => {
+            // Any number of negated values are allowed, but only one positive value and not both.
+            let mut others = filters.iter().filter(same);
+            if v.negated {
+                !others.any(|other| positive(&other))
+            } else {
+                others.next().is_none()
+            }
+        }
+        _ => !filters.iter().any(|other| same(&other)),
+    }
+}
+
+fn selector(input: &str) -> Result<(EntitySelector, &str)> {
+    let kind = match input.chars().nth(1) {
+        Some(c) => SelectorKind::from_char(c).ok_or_else(|| {
+            anyhow!(Message::translatable(
+                "argument.entity.selector.unknown",
+                vec![Message::text(format!("@{}", c))]
+            ))
+        })?,
+        None => bail!(Message::translatable(
+            "argument.entity.selector.missing",
+            vec![]
+        )),
+    };
+    let mut out = &input[2..];
+
+    let mut filters = Vec::new();
+    if let Some(rest) = out.strip_prefix('[') {
+        out = rest.trim_start();
+        if let Some(rest) = out.strip_prefix(']') {
+            out = rest;
+        } else {
+            loop {
+                let (key, rest) = take_while(out, |c| c.is_ascii_lowercase() || c == '_');
+                let rest = rest.trim_start().strip_prefix('=').ok_or_else(|| {
+                    anyhow!(Message::translatable(
+                        "argument.entity.options.valueless",
+                        vec![Message::text(key)]
+                    ))
+                })?;
+                let (filter, rest) = filter(key, rest.trim_start())?;
+                if !applicable(kind, &filters, &filter) {
+                    bail!(Message::translatable(
+                        "argument.entity.options.inapplicable",
+                        vec![Message::text(key)]
+                    ));
+                }
+                filters.push(filter);
+
+                let rest = rest.trim_start();
+                if let Some(rest) = rest.strip_prefix(',') {
+                    out = rest.trim_start();
+                } else if let Some(rest) = rest.strip_prefix(']') {
+                    out = rest;
+                    break;
+                } else {
+                    bail!(Message::translatable(
+                        "argument.entity.options.unterminated",
+                        vec![]
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok((EntitySelector::Selector { kind, filters }, out))
+}
+
+/// Parses a uuid in the hyphenated form Java accepts, like `f81d4fae-7dec-11d0-a765-00a0c91e6bf6`.
+fn uuid(value: &str) -> Option<u128> {
+    let parts = value.split('-').collect::<Vec<_>>();
+    let widths = [8, 4, 4, 4, 12];
+    if parts.len() != widths.len() {
+        return None;
+    }
+
+    let mut uuid = 0u128;
+    for (part, width) in parts.iter().zip(widths.iter()) {
+        // from_str_radix also accepts a leading '+', which Java does not.
+        if part.is_empty() || part.len() > *width || !part.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let value = u64::from_str_radix(part, 16).ok()?;
+        uuid = (uuid << (width * 4)) | u128::from(value);
+    }
+    Some(uuid)
+}
+
+/// Parses an [`EntitySelector`]. By default any number of entities of any type may be selected.
+#[derive(Default)]
+pub struct EntitySelectorParser {
+    separator: Separator,
+    single: bool,
+    players_only: bool,
+}
+
+impl EntitySelectorParser {
+    /// A parser that rejects selectors which can select more than one entity if `single` is set, and
+    /// selectors which can select entities other than players if `players_only` is set.
+    pub fn new(single: bool, players_only: bool) -> Self {
+        Self {
+            single,
+            players_only,
+            ..Self::default()
+        }
+    }
+}
+
+impl IterParser for EntitySelectorParser {
+    type Extract = (EntitySelector,);
+    type ParserState = ();
+
+    #[allow(clippy::type_complexity)]
+    fn parse<'p>(
+        &self,
+        _state: Self::ParserState,
+        input: &'p str,
+    ) -> (Result<(Self::Extract, &'p str)>, Option<Self::ParserState>) {
+        let result = if input.starts_with('@') {
+            selector(input)
+        } else {
+            let (value, out) = take_while(input, |c| !self.separator.is_separator(c));
+            if value.is_empty() {
+                Err(anyhow!(Message::translatable(
+                    "argument.entity.invalid",
+                    vec![]
+                )))
+            } else if let Some(uuid) = uuid(value) {
+                Ok((EntitySelector::Uuid(uuid), out))
+            } else if value.chars().count() <= 16 {
+                Ok((EntitySelector::Player(String::from(value)), out))
+            } else {
+                Err(anyhow!(Message::translatable(
+                    "argument.entity.invalid",
+                    vec![]
+                )))
+            }
+        };
+
+        let result = result.and_then(|(selector, out)| {
+            if !self.separator.is_boundary(out) {
+                bail!(Message::translatable("command.expected.separator", vec![]));
+            }
+            if self.single && selector.max_results() != Some(1) {
+                let key = if self.players_only {
+                    "argument.player.toomany"
+                } else {
+                    "argument.entity.toomany"
+                };
+                bail!(Message::translatable(key, vec![]));
+            }
+            if self.players_only && selector.includes_entities() {
+                bail!(Message::translatable("argument.player.entities", vec![]));
+            }
+            Ok(((selector,), out))
+        });
+        (result, None)
+    }
+
+    fn regex(&self) -> String {
+        // Quoted strings and nbt in the filters can contain anything, so they are not described any further.
+        let separator = self.separator.class();
+        format!("(@[aeprs](\\[.*\\])?|[^@{0}][^{0}]*)", separator)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument(
+            "targets",
+            ArgumentType::Entity {
+                single: self.single,
+                players_only: self.players_only,
+            },
+        )]]
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        let suggestions = |start: usize, partial: &str, options: &[&str], suffix: &str| {
+            options
+                .iter()
+                .filter(|option| option.starts_with(partial))
+                .map(|option| Suggestion {
+                    start,
+                    text: format!("{}{}", option, suffix),
+                })
+                .collect()
+        };
+
+        let start = match input.char_indices().nth(2) {
+            None if input.is_empty() || input == "@" => {
+                return suggestions(0, input, &["@p", "@a", "@r", "@s", "@e"], "")
+            }
+            Some((i, '[')) if input.starts_with('@') => i + 1,
+            _ => return Vec::new(),
+        };
+
+        // Find where the filter being written starts, skipping over quoted and nested values.
+        let mut segment = start;
+        let mut depth = 0;
+        let mut quote = None;
+        for (i, c) in input[start..].char_indices() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (None, '{') | (None, '[') => depth += 1,
+                (None, '}') => depth -= 1,
+                (None, ']') if depth == 0 => return Vec::new(),
+                (None, ']') => depth -= 1,
+                (None, ',') if depth == 0 => segment = start + i + 1,
+                _ => {}
+            }
+        }
+
+        let current = &input[segment..];
+        let offset = segment + (current.len() - current.trim_start().len());
+        let current = current.trim_start();
+        match current.find('=') {
+            None => suggestions(offset, current, KEYS, "="),
+            Some(eq) => {
+                let value = &current[eq + 1..];
+                let value_start = offset + eq + 1 + (value.len() - value.trim_start().len());
+                let value = value.trim_start();
+                let (value_start, value) = match value.strip_prefix('!') {
+                    Some(value) => (value_start + 1, value),
+                    None => (value_start, value),
+                };
+                match current[..eq].trim_end() {
+                    "sort" => suggestions(value_start, value, SORTS, ""),
+                    "gamemode" => suggestions(value_start, value, GAMEMODES, ""),
+                    _ => Vec::new(),
+                }
+            }
+        }
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.separator = separator.clone();
+    }
+}
+
+impl Argument for EntitySelector {
+    type Parser = EntitySelectorParser;
+    type ParserState = ();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{IterParser, Suggestion};
+    use crate::regex::{DFA, NFA};
+
+    use super::*;
+
+    fn parse(input: &str) -> anyhow::Result<EntitySelector> {
+        let ((selector,), out) = EntitySelectorParser::default().parse((), input).0?;
+        assert_eq!(out, "");
+        Ok(selector)
+    }
+
+    #[test]
+    fn targets() {
+        assert_eq!(
+            parse("Notch").unwrap(),
+            EntitySelector::Player(String::from("Notch"))
+        );
+        assert_eq!(
+            parse("f81d4fae-7dec-11d0-a765-00a0c91e6bf6").unwrap(),
+            EntitySelector::Uuid(0xf81d4fae_7dec_11d0_a765_00a0c91e6bf6)
+        );
+        assert_eq!(
+            parse("@s").unwrap(),
+            EntitySelector::Selector {
+                kind: SelectorKind::Executor,
+                filters: Vec::new()
+            }
+        );
+        assert!(parse("@x").is_err());
+        assert!(parse("ThisNameIsWayTooLong").is_err());
+        assert_eq!(uuid("+81d4fae-7dec-11d0-a765-00a0c91e6bf6"), None);
+        assert_eq!(uuid("f81d4fae-7dec-11d0-a765-+0a0c91e6bf6"), None);
+    }
+
+    #[test]
+    fn amount() {
+        let player = EntitySelectorParser::new(true, true);
+        for input in &[
+            "Notch",
+            "@p",
+            "@s",
+            "@a[limit=1]",
+            "@e[type=player,limit=1]",
+        ] {
+            assert!(player.parse((), input).0.is_ok(), "{}", input);
+        }
+        for input in &[
+            "@a",
+            "@r[limit=2]",
+            "@e[limit=1]",
+            "@e[type=!player,limit=1]",
+        ] {
+            assert!(player.parse((), input).0.is_err(), "{}", input);
+        }
+        let error = player.parse((), "@a").0.unwrap_err();
+        assert_eq!(
+            Message::from(&error),
+            Message::translatable("argument.player.toomany", vec![])
+        );
+
+        let entity = EntitySelectorParser::new(true, false);
+        assert!(entity.parse((), "@e[limit=1]").0.is_ok());
+        assert!(entity
+            .parse((), "f81d4fae-7dec-11d0-a765-00a0c91e6bf6")
+            .0
+            .is_ok());
+        assert!(entity.parse((), "@e").0.is_err());
+
+        let players = EntitySelectorParser::new(false, true);
+        assert!(players.parse((), "@a").0.is_ok());
+        assert!(players.parse((), "@e").0.is_err());
+
+        assert_eq!(
+            player.brigadier(),
+            vec![vec![NodeKind::argument(
+                "targets",
+                ArgumentType::Entity {
+                    single: true,
+                    players_only: true
+                }
+            )]]
+        );
+    }
+
+    #[test]
+    fn filters() {
+        let selector =
+            parse("@e[type=zombie, distance=..10,limit=3,sort=nearest,tag=!foo,name=\"A b\"]")
+                .unwrap();
+        let negatable = |negated, value: &str| Negatable {
+            negated,
+            value: String::from(value),
+        };
+        assert_eq!(
+            selector,
+            EntitySelector::Selector {
+                kind: SelectorKind::AllEntities,
+                filters: vec![
+                    SelectorFilter::Type(negatable(false, "zombie")),
+                    SelectorFilter::Distance(FloatRange {
+                        min: None,
+                        max: Some(10.0)
+                    }),
+                    SelectorFilter::Limit(3),
+                    SelectorFilter::Sort(Sort::Nearest),
+                    SelectorFilter::Tag(negatable(true, "foo")),
+                    SelectorFilter::Name(negatable(false, "A b")),
+                ]
+            }
+        );
+
+        let ((_,), out) = EntitySelectorParser::default()
+            .parse((), "@a[nbt={Tags:[\"a]\"]},scores={kills=1..}] rest")
+            .0
+            .unwrap();
+        assert_eq!(out, " rest");
+
+        for input in &[
+            "@e[limit=0]",
+            "@e[foo=1]",
+            "@e[x=1,x=2]",
+            "@s[limit=1]",
+            "@a[type=zombie]",
+            "@e[type=!zombie,type=cow]",
+            "@e[tag=a",
+            "@e[sort=up]",
+            "@e[distance=-1..]",
+            "@a[level=5..1]",
+        ] {
+            assert!(parse(input).is_err(), "{}", input);
+        }
+        assert!(parse("@e[type=!zombie,type=!cow,tag=a,tag=b]").is_ok());
+    }
+
+    #[test]
+    fn regex() {
+        let regex = EntitySelectorParser::default().regex();
+        let dfa = DFA::<usize>::from(NFA::<usize>::regex(&regex).unwrap());
+        for input in &["@p", "@e[type=zombie,name=\"a b\"]", "Notch"] {
+            assert!(dfa.find(input).is_ok(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn separator() {
+        let mut parser = EntitySelectorParser::default();
+        parser.set_separator(&Separator::chars([',']).unwrap());
+        let ((selector,), out) = parser.parse((), "Notch,5").0.unwrap();
+        assert_eq!(
+            (selector, out),
+            (EntitySelector::Player(String::from("Notch")), ",5")
+        );
+        assert_eq!(parser.parse((), "@s,5").0.unwrap().1, ",5");
+        assert!(parser.parse((), "@s 5").0.is_err());
+
+        let dfa = DFA::<usize>::from(NFA::<usize>::regex(&parser.regex()).unwrap());
+        assert!(dfa.find("Notch").is_ok());
+        assert!(dfa.find("Notch,5").is_err());
+    }
+
+    #[test]
+    fn suggest() {
+        let texts = |input| {
+            EntitySelectorParser::default()
+                .suggest(input)
+                .into_iter()
+                .map(|Suggestion { start, text }| (start, text))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts("").len(), 5);
+        assert_eq!(
+            texts("@e[type=zombie,dis"),
+            vec![(15, String::from("distance="))]
+        );
+        assert_eq!(texts("@e[sort=ne"), vec![(8, String::from("nearest"))]);
+        assert_eq!(
+            texts("@a[gamemode=!s"),
+            vec![
+                (13, String::from("survival")),
+                (13, String::from("spectator"))
+            ]
+        );
+        assert!(texts("@e[tag=a] ").is_empty());
+    }
+}
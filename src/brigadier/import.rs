@@ -0,0 +1,925 @@
+//! Reads the `commands.json` report of the vanilla data generator, so the commands of a server can be
+//! compared against vanilla and tested with the real grammar.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use indexmap::IndexMap;
+
+use crate::argument::{
+    BlockPosParser, BlockPredicateParser, BlockStateParser, DurationParser, EntitySelectorParser,
+    FloatRangeParser, IntRangeParser, ItemPredicateParser, ItemStackParser, Json, JsonParser, Nbt,
+    Registries, ResourceLocationParser, SnbtParser, Vec2Parser, Vec3Parser,
+};
+use crate::command::{FamilyCommand, GameStateFamily, Redirect};
+use crate::parser::{IterParser, Literal, Separator};
+use crate::source::{CommandSource, SourceFamily};
+
+use super::{ArgumentType, CommandTree, Node, NodeKind, StringKind};
+
+fn property<'a>(properties: Option<&'a IndexMap<String, Json>>, key: &str) -> Option<&'a Json> {
+    properties.and_then(|properties| properties.get(key))
+}
+
+fn bound<T>(
+    properties: Option<&IndexMap<String, Json>>,
+    key: &str,
+    convert: impl Fn(f64) -> T,
+) -> Result<Option<T>> {
+    match property(properties, key) {
+        None => Ok(None),
+        Some(Json::Number(value)) => Ok(Some(convert(*value))),
+        Some(_) => bail!("The {} of a number argument is not a number", key),
+    }
+}
+
+fn string_property<'a>(
+    properties: Option<&'a IndexMap<String, Json>>,
+    key: &str,
+) -> Result<&'a str> {
+    match property(properties, key) {
+        Some(Json::String(value)) => Ok(value),
+        _ => bail!("Expected the property '{}' to be a string", key),
+    }
+}
+
+impl ArgumentType {
+    /// Reads a parser as `commands.json` describes it. Properties of parsers that end up as
+    /// [`ArgumentType::Other`] are dropped.
+    pub fn from_json(
+        identifier: &str,
+        properties: Option<&IndexMap<String, Json>>,
+    ) -> Result<Self> {
+        Ok(match identifier {
+            "brigadier:bool" => ArgumentType::Bool,
+            "brigadier:float" => ArgumentType::Float {
+                min: bound(properties, "min", |v| v as f32)?,
+                max: bound(properties, "max", |v| v as f32)?,
+            },
+            "brigadier:double" => ArgumentType::Double {
+                min: bound(properties, "min", |v| v)?,
+                max: bound(properties, "max", |v| v)?,
+            },
+            "brigadier:integer" => ArgumentType::Integer {
+                min: bound(properties, "min", |v| v as i32)?,
+                max: bound(properties, "max", |v| v as i32)?,
+            },
+            "brigadier:long" => ArgumentType::Long {
+                min: bound(properties, "min", |v| v as i64)?,
+                max: bound(properties, "max", |v| v as i64)?,
+            },
+            "brigadier:string" => {
+                ArgumentType::String(match string_property(properties, "type")? {
+                    "word" => StringKind::SingleWord,
+                    "phrase" => StringKind::QuotablePhrase,
+                    "greedy" => StringKind::GreedyPhrase,
+                    other => bail!("Unknown string type '{}'", other),
+                })
+            }
+            "minecraft:entity" => ArgumentType::Entity {
+                single: string_property(properties, "amount")? == "single",
+                players_only: string_property(properties, "type")? == "players",
+            },
+            "minecraft:block_pos" => ArgumentType::BlockPos,
+            "minecraft:column_pos" => ArgumentType::ColumnPos,
+            "minecraft:vec3" => ArgumentType::Vec3,
+            "minecraft:vec2" => ArgumentType::Vec2,
+            "minecraft:block_state" => ArgumentType::BlockState,
+            "minecraft:block_predicate" => ArgumentType::BlockPredicate,
+            "minecraft:item_stack" => ArgumentType::ItemStack,
+            "minecraft:item_predicate" => ArgumentType::ItemPredicate,
+            "minecraft:component" => ArgumentType::Component,
+            "minecraft:nbt_compound_tag" => ArgumentType::NbtCompoundTag,
+            "minecraft:nbt_tag" => ArgumentType::NbtTag,
+            "minecraft:resource_location" => ArgumentType::ResourceLocation,
+            "minecraft:time" => ArgumentType::Time,
+            "minecraft:int_range" => ArgumentType::IntRange,
+            "minecraft:float_range" => ArgumentType::FloatRange,
+            other => ArgumentType::Other(String::from(other)),
+        })
+    }
+}
+
+/// Two nodes match the same way if they are the same literal, or arguments with the same parser and
+/// properties. Argument names and suggestions are only labels, so they are not compared.
+fn same_shape(a: &NodeKind, b: &NodeKind) -> bool {
+    match (a, b) {
+        (NodeKind::Argument { parser: a, .. }, NodeKind::Argument { parser: b, .. }) => a == b,
+        (a, b) => a == b,
+    }
+}
+
+impl CommandTree {
+    /// Reads a tree in the format of the `commands.json` report, where children are keyed by their name
+    /// and redirects are given as the names of the nodes leading to the target.
+    pub fn from_json(input: &str) -> Result<Self> {
+        let ((json,), rest) = JsonParser.parse((), input).0?;
+        if !rest.trim().is_empty() {
+            bail!("Unexpected input after the command tree");
+        }
+
+        let mut tree = CommandTree { nodes: Vec::new() };
+        let mut redirects = Vec::new();
+        tree.read_node(None, &json, &mut redirects)?;
+
+        for (node, path) in redirects {
+            let mut target = Self::ROOT;
+            for name in &path {
+                target = *tree.nodes[target]
+                    .children
+                    .iter()
+                    .find(|&&child| tree.nodes[child].kind.name() == Some(name.as_str()))
+                    .ok_or_else(|| anyhow!("Redirect to unknown node '{}'", path.join(" ")))?;
+            }
+            tree.nodes[node].redirect = Some(target);
+        }
+        Ok(tree)
+    }
+
+    fn read_node(
+        &mut self,
+        name: Option<&str>,
+        json: &Json,
+        redirects: &mut Vec<(usize, Vec<String>)>,
+    ) -> Result<usize> {
+        let fields = match json {
+            Json::Object(fields) => fields,
+            _ => bail!("Expected a node to be an object"),
+        };
+        let properties = match fields.get("properties") {
+            Some(Json::Object(properties)) => Some(properties),
+            Some(_) => bail!("Expected the properties of a node to be an object"),
+            None => None,
+        };
+
+        let kind = match (string_property(Some(fields), "type")?, name) {
+            ("root", None) => NodeKind::Root,
+            ("literal", Some(name)) => NodeKind::Literal(String::from(name)),
+            ("argument", Some(name)) => {
+                let parser = string_property(Some(fields), "parser")?;
+                NodeKind::argument(name, ArgumentType::from_json(parser, properties)?)
+            }
+            (_, None) => bail!("Expected the tree to start with a root node"),
+            (kind, Some(name)) => bail!("Node '{}' has the unexpected type '{}'", name, kind),
+        };
+
+        let id = self.nodes.len();
+        self.nodes.push(Node::new(kind));
+        match fields.get("executable") {
+            Some(Json::Bool(executable)) => self.nodes[id].executable = *executable,
+            Some(_) => bail!("Expected 'executable' to be a boolean"),
+            None => {}
+        }
+        match fields.get("redirect") {
+            Some(Json::Array(path)) => {
+                let path = path
+                    .iter()
+                    .map(|name| match name {
+                        Json::String(name) => Ok(name.clone()),
+                        _ => Err(anyhow!("Expected a redirect to be a list of names")),
+                    })
+                    .collect::<Result<_>>()?;
+                redirects.push((id, path));
+            }
+            Some(_) => bail!("Expected a redirect to be a list of names"),
+            None => {}
+        }
+        match fields.get("children") {
+            Some(Json::Object(children)) => {
+                for (name, child) in children {
+                    let child = self.read_node(Some(name), child, redirects)?;
+                    self.nodes[id].children.push(child);
+                }
+            }
+            Some(_) => bail!("Expected the children of a node to be an object"),
+            None => {}
+        }
+        Ok(id)
+    }
+
+    /// Lists every way to form a complete command. Redirects are followed unless they lead back to a
+    /// node that is already on the path, so aliases like `/tp` are listed while `/execute run` is not.
+    pub fn paths(&self) -> Vec<Vec<NodeKind>> {
+        fn walk(
+            tree: &CommandTree,
+            node: usize,
+            visited: &mut Vec<usize>,
+            path: &mut Vec<NodeKind>,
+            paths: &mut Vec<Vec<NodeKind>>,
+        ) {
+            visited.push(node);
+            if tree.nodes[node].executable {
+                paths.push(path.clone());
+            }
+            let next = match tree.nodes[node].redirect {
+                Some(target) if visited.contains(&target) => None,
+                Some(target) => Some(target),
+                None => Some(node),
+            };
+            if let Some(next) = next {
+                for &child in &tree.nodes[next].children {
+                    path.push(tree.nodes[child].kind.clone());
+                    walk(tree, child, visited, path, paths);
+                    path.pop();
+                }
+            }
+            visited.pop();
+        }
+
+        let mut paths = Vec::new();
+        walk(
+            self,
+            Self::ROOT,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut paths,
+        );
+        paths
+    }
+
+    /// Returns the paths of this tree that `implemented` has no path for, like the vanilla subcommands a
+    /// server is still missing.
+    pub fn missing(&self, implemented: &CommandTree) -> Vec<Vec<NodeKind>> {
+        let implemented = implemented.paths();
+        self.paths()
+            .into_iter()
+            .filter(|path| {
+                !implemented.iter().any(|other| {
+                    other.len() == path.len()
+                        && other.iter().zip(path).all(|(a, b)| same_shape(a, b))
+                })
+            })
+            .collect()
+    }
+
+    /// Turns every path into a command that only checks the grammar. Like other commands, the stubs
+    /// expect a leading `/`, so they can be registered next to real commands.
+    pub fn stub_commands(&self) -> Vec<StubCommand> {
+        self.paths()
+            .into_iter()
+            .map(|mut path| {
+                slash(&mut path);
+                StubCommand::new(path)
+            })
+            .collect()
+    }
+
+    /// Turns the redirects that [`CommandTree::paths`] does not follow, like `/execute run`, into redirects
+    /// that only check the grammar. Registered next to the [`CommandTree::stub_commands`], they let
+    /// [`Dispatcher::call_forked`](crate::dispatcher::Dispatcher::call_forked) check input like
+    /// `/execute as @a run say hi`. Redirects to a node that can not be written as literals are left out.
+    pub fn stub_redirects(&self) -> Vec<StubRedirect> {
+        fn walk(
+            tree: &CommandTree,
+            node: usize,
+            ancestors: &mut Vec<usize>,
+            path: &mut Vec<NodeKind>,
+            redirects: &mut Vec<StubRedirect>,
+        ) {
+            ancestors.push(node);
+            if let Some(target) = tree.nodes[node].redirect {
+                // The target is reached through children only, so its ancestors lead to it.
+                if let Some(depth) = ancestors.iter().position(|&ancestor| ancestor == target) {
+                    let words = ancestors[1..=depth]
+                        .iter()
+                        .map(|&ancestor| match &tree.nodes[ancestor].kind {
+                            NodeKind::Literal(word) => Some(word.clone()),
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<_>>>();
+                    if let Some(words) = words {
+                        let mut path = path.clone();
+                        slash(&mut path);
+                        redirects.push(StubRedirect::new(path, words));
+                    }
+                }
+            }
+            for &child in &tree.nodes[node].children {
+                path.push(tree.nodes[child].kind.clone());
+                walk(tree, child, ancestors, path, redirects);
+                path.pop();
+            }
+            ancestors.pop();
+        }
+
+        let mut redirects = Vec::new();
+        walk(
+            self,
+            Self::ROOT,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut redirects,
+        );
+        redirects
+    }
+}
+
+/// Commands are registered with a leading `/`, which the tree of the client leaves out.
+fn slash(path: &mut [NodeKind]) {
+    if let Some(NodeKind::Literal(first)) = path.first_mut() {
+        first.insert(0, '/');
+    }
+}
+
+/// Gives a parser the separator of the command it is part of.
+fn separated<P: IterParser>(mut parser: P, separator: &Separator) -> P {
+    parser.set_separator(separator);
+    parser
+}
+
+fn run<'i, P: IterParser>(parser: P, separator: &Separator, input: &'i str) -> Result<&'i str> {
+    separated(parser, separator)
+        .parse(P::ParserState::default(), input)
+        .0
+        .map(|(_, out)| out)
+}
+
+fn number<'i, T>(
+    input: &'i str,
+    separator: &Separator,
+    min: Option<T>,
+    max: Option<T>,
+) -> Result<&'i str>
+where
+    T: FromStr + PartialOrd + Display + Copy,
+{
+    let end = input
+        .find(|c| separator.is_separator(c))
+        .unwrap_or(input.len());
+    let value: T = input[..end]
+        .parse()
+        .map_err(|_| anyhow!("Expected a number"))?;
+    if let Some(min) = min.filter(|min| value < *min) {
+        bail!("The number {} is smaller than the minimum {}", value, min);
+    }
+    if let Some(max) = max.filter(|max| value > *max) {
+        bail!("The number {} is larger than the maximum {}", value, max);
+    }
+    Ok(&input[end..])
+}
+
+fn unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '+'
+}
+
+fn word(input: &str) -> Result<&str> {
+    let end = input.find(|c| !unquoted_char(c)).unwrap_or(input.len());
+    if end == 0 {
+        bail!("Expected a word");
+    }
+    Ok(&input[end..])
+}
+
+fn quotable(input: &str) -> Result<&str> {
+    let quote = match input.chars().next() {
+        Some(quote) if quote == '"' || quote == '\'' => quote,
+        _ => return word(input),
+    };
+    let mut escaped = false;
+    for (i, c) in input.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return Ok(&input[i + 1..]),
+            _ => {}
+        }
+    }
+    bail!("Unclosed quoted string")
+}
+
+/// Parses one argument and returns the rest of the input.
+fn parse_argument<'p>(
+    parser: &ArgumentType,
+    separator: &Separator,
+    input: &'p str,
+) -> Result<&'p str> {
+    match parser {
+        ArgumentType::Bool => ["true", "false"]
+            .iter()
+            .find_map(|value| input.strip_prefix(value))
+            .ok_or_else(|| anyhow!("Expected true or false")),
+        ArgumentType::Float { min, max } => number(input, separator, *min, *max),
+        ArgumentType::Double { min, max } => number(input, separator, *min, *max),
+        ArgumentType::Integer { min, max } => number(input, separator, *min, *max),
+        ArgumentType::Long { min, max } => number(input, separator, *min, *max),
+        ArgumentType::String(StringKind::SingleWord) => word(input),
+        ArgumentType::String(StringKind::QuotablePhrase) => quotable(input),
+        ArgumentType::String(StringKind::GreedyPhrase) => Ok(""),
+        ArgumentType::Entity {
+            single,
+            players_only,
+        } => run(
+            EntitySelectorParser::new(*single, *players_only),
+            separator,
+            input,
+        ),
+
+        ArgumentType::BlockPos => run(BlockPosParser::default(), separator, input),
+        // Column positions are whole numbers, but the grammar is the same as for two coordinates.
+        ArgumentType::ColumnPos | ArgumentType::Vec2 => {
+            run(Vec2Parser::default(), separator, input)
+        }
+        ArgumentType::Vec3 => run(Vec3Parser::default(), separator, input),
+        ArgumentType::BlockState => run(BlockStateParser, separator, input),
+        ArgumentType::BlockPredicate => run(BlockPredicateParser, separator, input),
+        ArgumentType::ItemStack => run(ItemStackParser, separator, input),
+        ArgumentType::ItemPredicate => run(ItemPredicateParser, separator, input),
+        ArgumentType::Component => run(JsonParser, separator, input),
+        ArgumentType::NbtCompoundTag => match SnbtParser.parse((), input).0? {
+            ((Nbt::Compound(_),), out) => Ok(out),
+            _ => bail!("Expected a compound"),
+        },
+        ArgumentType::NbtTag => run(SnbtParser, separator, input),
+        ArgumentType::ResourceLocation => run(ResourceLocationParser::default(), separator, input),
+        ArgumentType::Time => run(DurationParser::default(), separator, input),
+        ArgumentType::IntRange => run(IntRangeParser, separator, input),
+        ArgumentType::FloatRange => run(FloatRangeParser, separator, input),
+        // Chat messages take the rest of the input, other unknown parsers a single word.
+        ArgumentType::Other(identifier) if identifier == "minecraft:message" => Ok(""),
+        ArgumentType::Other(_) => {
+            let end = input
+                .find(|c| separator.is_separator(c))
+                .unwrap_or(input.len());
+            if end == 0 {
+                bail!("Expected an argument");
+            }
+            Ok(&input[end..])
+        }
+    }
+}
+
+fn argument_regex(parser: &ArgumentType, separator: &Separator) -> String {
+    let unquoted = "[0-9A-Za-z_\\.\\+\\-]+";
+    let integer = "-?\\d+";
+    let float = "-?(\\d+(\\.\\d*)?|\\.\\d+)";
+    match parser {
+        ArgumentType::Bool => String::from("(true|false)"),
+        ArgumentType::Float { .. } | ArgumentType::Double { .. } => String::from(float),
+        ArgumentType::Integer { .. } | ArgumentType::Long { .. } => String::from(integer),
+        ArgumentType::String(StringKind::SingleWord) => String::from(unquoted),
+        ArgumentType::String(StringKind::QuotablePhrase) => {
+            format!("(\"([^\"\\\\]|\\\\.)*\"|'([^'\\\\]|\\\\.)*'|{})", unquoted)
+        }
+        ArgumentType::String(StringKind::GreedyPhrase) => String::from(".*"),
+        ArgumentType::Entity { .. } => {
+            separated(EntitySelectorParser::default(), separator).regex()
+        }
+        ArgumentType::BlockPos => separated(BlockPosParser::default(), separator).regex(),
+        ArgumentType::ColumnPos | ArgumentType::Vec2 => {
+            separated(Vec2Parser::default(), separator).regex()
+        }
+        ArgumentType::Vec3 => separated(Vec3Parser::default(), separator).regex(),
+        ArgumentType::BlockState => BlockStateParser.regex(),
+        ArgumentType::BlockPredicate => BlockPredicateParser.regex(),
+        ArgumentType::ItemStack => ItemStackParser.regex(),
+        ArgumentType::ItemPredicate => ItemPredicateParser.regex(),
+        ArgumentType::Component => JsonParser.regex(),
+        ArgumentType::NbtCompoundTag | ArgumentType::NbtTag => SnbtParser.regex(),
+        ArgumentType::ResourceLocation => ResourceLocationParser::default().regex(),
+        ArgumentType::Time => DurationParser::default().regex(),
+        ArgumentType::IntRange => IntRangeParser.regex(),
+        ArgumentType::FloatRange => FloatRangeParser.regex(),
+        ArgumentType::Other(identifier) if identifier == "minecraft:message" => String::from(".*"),
+        ArgumentType::Other(_) => String::from("\\S+"),
+    }
+}
+
+/// A command made from a path of a [`CommandTree`], which accepts input that follows the path and
+/// returns the path. Arguments are checked with the parsers lieutenant has for them, without registries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StubCommand {
+    path: Vec<NodeKind>,
+    separator: Separator,
+}
+
+impl StubCommand {
+    pub fn new(path: Vec<NodeKind>) -> Self {
+        Self {
+            path,
+            separator: Separator::default(),
+        }
+    }
+
+    pub fn path(&self) -> &[NodeKind] {
+        &self.path
+    }
+
+    fn parse(&self, input: &str) -> Result<()> {
+        if !parse_path(&self.path, &self.separator, input)?.is_empty() {
+            bail!("Unexpected input after the command");
+        }
+        Ok(())
+    }
+}
+
+/// Parses the nodes of a path with separators between them, and returns the rest of the input.
+fn parse_path<'i>(path: &[NodeKind], separator: &Separator, input: &'i str) -> Result<&'i str> {
+    let mut input = input;
+    for (i, kind) in path.iter().enumerate() {
+        if i != 0 {
+            input = separator
+                .strip(input)
+                .ok_or_else(|| anyhow!("Expected whitespace between arguments"))?;
+        }
+        input = match kind {
+            NodeKind::Root => input,
+            NodeKind::Literal(value) => run(Literal::new(value.clone()), separator, input)?,
+            NodeKind::Argument { parser, .. } => parse_argument(parser, separator, input)?,
+        };
+    }
+    Ok(input)
+}
+
+fn path_regex(path: &[NodeKind], separator: &Separator) -> String {
+    let parts: Vec<String> = path
+        .iter()
+        .map(|kind| match kind {
+            NodeKind::Root => String::new(),
+            NodeKind::Literal(value) => separated(Literal::new(value.clone()), separator).regex(),
+            NodeKind::Argument { parser, .. } => argument_regex(parser, separator),
+        })
+        .collect();
+    parts.join(&separator.regex())
+}
+
+impl<G: GameStateFamily> FamilyCommand<G> for StubCommand {
+    type CommandResult = Vec<NodeKind>;
+
+    fn call(&self, _gamestate: G::GameState<'_>, input: &str) -> Result<Vec<NodeKind>> {
+        self.parse(input)?;
+        Ok(self.path.clone())
+    }
+
+    fn regex(&self) -> String {
+        path_regex(&self.path, &self.separator)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![self.path.clone()]
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.separator = separator.clone();
+    }
+}
+
+/// A redirect made from a path of a [`CommandTree`] that ends at a redirect, like `/execute as <targets>`.
+/// It checks the path, keeps the source as it is, and continues with the rest of the input at the target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StubRedirect {
+    path: Vec<NodeKind>,
+    /// The literals leading to the target, empty for the root.
+    words: Vec<String>,
+    target: String,
+    separator: Separator,
+}
+
+impl StubRedirect {
+    pub fn new(path: Vec<NodeKind>, words: Vec<String>) -> Self {
+        let mut redirect = Self {
+            path,
+            words,
+            target: String::new(),
+            separator: Separator::default(),
+        };
+        redirect.set_target();
+        redirect
+    }
+
+    pub fn path(&self) -> &[NodeKind] {
+        &self.path
+    }
+
+    /// Checks the path at the start of the input, and returns the rest after the separator.
+    fn parse<'i>(&self, input: &'i str) -> Result<&'i str> {
+        let rest = parse_path(&self.path, &self.separator, input)?;
+        if !self.separator.is_boundary(rest) {
+            bail!("Unexpected input after the redirect");
+        }
+        Ok(self.separator.skip(rest))
+    }
+
+    fn set_target(&mut self) {
+        let separator = self.separator.canonical().to_string();
+        self.target = format!("/{}", self.words.join(&separator));
+        if !self.words.is_empty() {
+            self.target.push_str(&separator);
+        }
+    }
+}
+
+impl<G: SourceFamily> Redirect<G> for StubRedirect {
+    fn fork(
+        &self,
+        gamestate: G::GameState<'_>,
+        input: &str,
+    ) -> Result<(Vec<CommandSource>, String)> {
+        let rest = self.parse(input)?;
+        Ok((
+            vec![G::source(&gamestate).clone()],
+            format!("{}{}", self.target, rest),
+        ))
+    }
+
+    fn regex(&self) -> String {
+        format!(
+            "{}({}.*)?",
+            path_regex(&self.path, &self.separator),
+            self.separator.regex()
+        )
+    }
+
+    fn rest<'i>(&self, _registries: &dyn Registries, input: &'i str) -> Option<&'i str> {
+        self.parse(input).ok()
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![self.path.clone()]
+    }
+
+    fn target(&self) -> &str {
+        &self.target
+    }
+
+    fn forks(&self) -> bool {
+        false
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.separator = separator.clone();
+        self.set_target();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brigadier::{ArgumentType, CommandTree, NodeKind, StringKind};
+    use crate::dispatcher::Dispatcher;
+    use crate::source::{CommandSource, Sourced};
+
+    const COMMANDS: &str = r#"{
+        "type": "root",
+        "children": {
+            "time": {
+                "type": "literal",
+                "children": {
+                    "set": {
+                        "type": "literal",
+                        "children": {
+                            "time": {
+                                "type": "argument",
+                                "parser": "brigadier:integer",
+                                "properties": { "min": 0 },
+                                "executable": true
+                            }
+                        }
+                    },
+                    "query": {
+                        "type": "literal",
+                        "children": {
+                            "daytime": { "type": "literal", "executable": true }
+                        }
+                    }
+                }
+            },
+            "teleport": {
+                "type": "literal",
+                "children": {
+                    "targets": {
+                        "type": "argument",
+                        "parser": "minecraft:entity",
+                        "properties": { "amount": "multiple", "type": "entities" },
+                        "executable": true
+                    }
+                }
+            },
+            "tp": { "type": "literal", "redirect": ["teleport"] },
+            "execute": {
+                "type": "literal",
+                "children": {
+                    "as": {
+                        "type": "literal",
+                        "children": {
+                            "targets": {
+                                "type": "argument",
+                                "parser": "minecraft:entity",
+                                "properties": { "amount": "multiple", "type": "entities" },
+                                "redirect": ["execute"]
+                            }
+                        }
+                    },
+                    "run": { "type": "literal", "redirect": [] }
+                }
+            },
+            "say": {
+                "type": "literal",
+                "children": {
+                    "message": {
+                        "type": "argument",
+                        "parser": "minecraft:message",
+                        "executable": true
+                    }
+                }
+            },
+            "tag": {
+                "type": "literal",
+                "children": {
+                    "name": {
+                        "type": "argument",
+                        "parser": "brigadier:string",
+                        "properties": { "type": "word" },
+                        "executable": true
+                    }
+                }
+            }
+        }
+    }"#;
+
+    fn literal(value: &str) -> NodeKind {
+        NodeKind::Literal(String::from(value))
+    }
+
+    #[test]
+    fn import() {
+        let tree = CommandTree::from_json(COMMANDS).unwrap();
+        assert_eq!(tree.nodes.len(), 17);
+        assert_eq!(tree.nodes[0].kind, NodeKind::Root);
+
+        let node = |path: &[&str]| {
+            path.iter().fold(CommandTree::ROOT, |node, name| {
+                *tree.nodes[node]
+                    .children
+                    .iter()
+                    .find(|&&child| tree.nodes[child].kind.name() == Some(*name))
+                    .unwrap()
+            })
+        };
+        assert_eq!(
+            tree.nodes[node(&["tp"])].redirect,
+            Some(node(&["teleport"]))
+        );
+        assert_eq!(
+            tree.nodes[node(&["execute", "as", "targets"])].redirect,
+            Some(node(&["execute"]))
+        );
+        assert_eq!(
+            tree.nodes[node(&["execute", "run"])].redirect,
+            Some(CommandTree::ROOT)
+        );
+        assert_eq!(
+            tree.nodes[node(&["time", "set", "time"])].kind,
+            NodeKind::argument(
+                "time",
+                ArgumentType::Integer {
+                    min: Some(0),
+                    max: None
+                }
+            )
+        );
+        assert_eq!(
+            tree.nodes[node(&["say", "message"])].kind,
+            NodeKind::argument(
+                "message",
+                ArgumentType::Other(String::from("minecraft:message"))
+            )
+        );
+
+        for input in &[
+            "{\"type\": \"literal\"}",
+            "{\"type\": \"root\", \"children\": {\"a\": {\"type\": \"root\"}}}",
+            "{\"type\": \"root\", \"children\": {\"a\": {\"type\": \"literal\", \"redirect\": [\"b\"]}}}",
+            "{\"type\": \"root\"} {}",
+        ] {
+            assert!(CommandTree::from_json(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn missing() {
+        let vanilla = CommandTree::from_json(COMMANDS).unwrap();
+        let paths = vanilla.paths();
+        assert_eq!(paths.len(), 6);
+        assert!(paths.contains(&vec![
+            literal("tp"),
+            NodeKind::argument(
+                "targets",
+                ArgumentType::Entity {
+                    single: false,
+                    players_only: false
+                }
+            )
+        ]));
+
+        let mut implemented = CommandTree::new();
+        implemented.add_path(&[
+            literal("time"),
+            literal("set"),
+            NodeKind::argument(
+                "ticks",
+                ArgumentType::Integer {
+                    min: Some(0),
+                    max: None,
+                },
+            ),
+        ]);
+        implemented.add_path(&[
+            literal("tag"),
+            NodeKind::argument("name", ArgumentType::String(StringKind::SingleWord)),
+        ]);
+
+        let missing = vanilla.missing(&implemented);
+        assert_eq!(missing.len(), 4);
+        assert!(!missing
+            .iter()
+            .any(|path| path[0] == literal("time") && path[1] == literal("set")));
+        assert!(!missing.iter().any(|path| path[0] == literal("tag")));
+        assert!(missing.iter().any(|path| path[0] == literal("tp")));
+
+        // Arguments only match with the same properties.
+        let mut different = CommandTree::new();
+        different.add_path(&[
+            literal("time"),
+            literal("set"),
+            NodeKind::argument(
+                "time",
+                ArgumentType::Integer {
+                    min: None,
+                    max: None,
+                },
+            ),
+        ]);
+        different.add_path(&[
+            literal("tag"),
+            NodeKind::argument("name", ArgumentType::String(StringKind::GreedyPhrase)),
+        ]);
+        assert_eq!(vanilla.missing(&different).len(), 6);
+    }
+
+    #[test]
+    fn stubs() {
+        let tree = CommandTree::from_json(COMMANDS).unwrap();
+        let mut dispatcher = Dispatcher::<(), Vec<NodeKind>>::new();
+        for command in tree.stub_commands() {
+            dispatcher.register(command).unwrap();
+        }
+
+        let path = dispatcher.call((), "/time set 1000").unwrap();
+        assert_eq!(path[1], literal("set"));
+        assert_eq!(dispatcher.call((), "/time query daytime").unwrap().len(), 3);
+        assert_eq!(
+            dispatcher.call((), "/tp @e[type=cow]").unwrap()[0],
+            literal("/tp")
+        );
+        assert!(dispatcher.call((), "/say hello there").is_ok());
+        assert!(dispatcher.call((), "/tag friendly").is_ok());
+
+        for input in &[
+            "/time set -5",
+            "/time set 10 20",
+            "/time query",
+            "/tag two words",
+            "/execute run say hi",
+        ] {
+            assert!(dispatcher.call((), input).is_err(), "{}", input);
+        }
+
+        assert_eq!(dispatcher.command_tree().paths().len(), 6);
+    }
+
+    #[test]
+    fn stub_redirects() {
+        let tree = CommandTree::from_json(COMMANDS).unwrap();
+        let redirects = tree.stub_redirects();
+        assert_eq!(redirects.len(), 2);
+        assert_eq!(redirects[1].path(), &[literal("/execute"), literal("run")]);
+
+        let mut dispatcher = Dispatcher::<Sourced<()>, Vec<NodeKind>>::new();
+        for command in tree.stub_commands() {
+            dispatcher.register(command).unwrap();
+        }
+        for redirect in redirects {
+            dispatcher.register_redirect(redirect).unwrap();
+        }
+
+        let source = CommandSource::console();
+        let call = |input| {
+            let mut results = dispatcher.call_forked((&mut (), &source), input);
+            assert_eq!(results.len(), 1, "{}", input);
+            results.pop().unwrap().1
+        };
+        assert_eq!(call("/execute run say hi").unwrap()[0], literal("/say"));
+        assert_eq!(
+            call("/execute as @a as @e[type=cow] run tag x").unwrap()[0],
+            literal("/tag")
+        );
+        for input in &["/execute as @a", "/execute as run say hi", "/execute run"] {
+            assert!(call(input).is_err(), "{}", input);
+        }
+
+        // The redirects are part of the exported tree again.
+        let exported = dispatcher.command_tree();
+        assert_eq!(exported.paths().len(), 6);
+        assert_eq!(exported.stub_redirects().len(), 2);
+    }
+}
@@ -0,0 +1,394 @@
+//! Exports commands as the node graph of Mojang's brigadier library, which the client receives in the
+//! Declare Commands packet to complete and highlight commands on its own.
+
+mod import;
+
+pub use import::{StubCommand, StubRedirect};
+
+/// The suggestions of an argument that the client asks the server for, see [`NodeKind::with_suggestions`].
+pub const ASK_SERVER: &str = "minecraft:ask_server";
+
+/// How brigadier parses an argument on the client, with the properties the protocol sends for it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentType {
+    Bool,
+    Float {
+        min: Option<f32>,
+        max: Option<f32>,
+    },
+    Double {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    Integer {
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+    Long {
+        min: Option<i64>,
+        max: Option<i64>,
+    },
+    String(StringKind),
+    Entity {
+        single: bool,
+        players_only: bool,
+    },
+    BlockPos,
+    ColumnPos,
+    Vec3,
+    Vec2,
+    BlockState,
+    BlockPredicate,
+    ItemStack,
+    ItemPredicate,
+    Component,
+    NbtCompoundTag,
+    NbtTag,
+    ResourceLocation,
+    Time,
+    IntRange,
+    FloatRange,
+    /// A parser lieutenant has no equivalent for, by its identifier. It is written without properties.
+    Other(String),
+}
+
+/// The ways `brigadier:string` reads a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringKind {
+    SingleWord,
+    QuotablePhrase,
+    GreedyPhrase,
+}
+
+impl ArgumentType {
+    /// The identifier of the parser in the protocol.
+    pub fn identifier(&self) -> &str {
+        match self {
+            ArgumentType::Bool => "brigadier:bool",
+            ArgumentType::Float { .. } => "brigadier:float",
+            ArgumentType::Double { .. } => "brigadier:double",
+            ArgumentType::Integer { .. } => "brigadier:integer",
+            ArgumentType::Long { .. } => "brigadier:long",
+            ArgumentType::String(_) => "brigadier:string",
+            ArgumentType::Entity { .. } => "minecraft:entity",
+            ArgumentType::BlockPos => "minecraft:block_pos",
+            ArgumentType::ColumnPos => "minecraft:column_pos",
+            ArgumentType::Vec3 => "minecraft:vec3",
+            ArgumentType::Vec2 => "minecraft:vec2",
+            ArgumentType::BlockState => "minecraft:block_state",
+            ArgumentType::BlockPredicate => "minecraft:block_predicate",
+            ArgumentType::ItemStack => "minecraft:item_stack",
+            ArgumentType::ItemPredicate => "minecraft:item_predicate",
+            ArgumentType::Component => "minecraft:component",
+            ArgumentType::NbtCompoundTag => "minecraft:nbt_compound_tag",
+            ArgumentType::NbtTag => "minecraft:nbt_tag",
+            ArgumentType::ResourceLocation => "minecraft:resource_location",
+            ArgumentType::Time => "minecraft:time",
+            ArgumentType::IntRange => "minecraft:int_range",
+            ArgumentType::FloatRange => "minecraft:float_range",
+            ArgumentType::Other(identifier) => identifier,
+        }
+    }
+
+    fn write_properties(&self, buf: &mut Vec<u8>) {
+        fn bounds<T>(
+            buf: &mut Vec<u8>,
+            min: &Option<T>,
+            max: &Option<T>,
+            bytes: impl Fn(&T) -> Vec<u8>,
+        ) {
+            buf.push(min.is_some() as u8 | (max.is_some() as u8) << 1);
+            for bound in min.iter().chain(max.iter()) {
+                buf.extend(bytes(bound));
+            }
+        }
+
+        match self {
+            ArgumentType::Float { min, max } => bounds(buf, min, max, |v| v.to_be_bytes().to_vec()),
+            ArgumentType::Double { min, max } => {
+                bounds(buf, min, max, |v| v.to_be_bytes().to_vec())
+            }
+            ArgumentType::Integer { min, max } => {
+                bounds(buf, min, max, |v| v.to_be_bytes().to_vec())
+            }
+            ArgumentType::Long { min, max } => bounds(buf, min, max, |v| v.to_be_bytes().to_vec()),
+            ArgumentType::String(kind) => write_var_int(buf, *kind as i32),
+            ArgumentType::Entity {
+                single,
+                players_only,
+            } => buf.push(*single as u8 | (*players_only as u8) << 1),
+            _ => {}
+        }
+    }
+}
+
+/// What a node matches: the root that every command starts from, a fixed word, or an argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Root,
+    Literal(String),
+    Argument {
+        name: String,
+        parser: ArgumentType,
+        /// Where the client gets suggestions from, like [`ASK_SERVER`]. Without it, the client completes
+        /// the argument with its parser.
+        suggestions: Option<String>,
+    },
+}
+
+impl NodeKind {
+    pub fn argument(name: &str, parser: ArgumentType) -> Self {
+        NodeKind::Argument {
+            name: String::from(name),
+            parser,
+            suggestions: None,
+        }
+    }
+
+    /// Makes the client get the suggestions of an argument from `suggestions`, like [`ASK_SERVER`]
+    /// for arguments it can not complete with their parser.
+    pub fn with_suggestions(self, suggestions: &str) -> Self {
+        match self {
+            NodeKind::Argument { name, parser, .. } => NodeKind::Argument {
+                name,
+                parser,
+                suggestions: Some(String::from(suggestions)),
+            },
+            kind => kind,
+        }
+    }
+
+    fn name(&self) -> Option<&str> {
+        match self {
+            NodeKind::Root => None,
+            NodeKind::Literal(name) | NodeKind::Argument { name, .. } => Some(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub kind: NodeKind,
+    /// If the input may end after this node.
+    pub executable: bool,
+    pub children: Vec<usize>,
+    /// The node parsing continues at after this one, like how `/execute run` continues at the root.
+    pub redirect: Option<usize>,
+}
+
+impl Node {
+    fn new(kind: NodeKind) -> Self {
+        Self {
+            kind,
+            executable: false,
+            children: Vec::new(),
+            redirect: None,
+        }
+    }
+}
+
+/// A brigadier command graph. Nodes refer to each other by their index, and the root is at index 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandTree {
+    pub nodes: Vec<Node>,
+}
+
+impl Default for CommandTree {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::new(NodeKind::Root)],
+        }
+    }
+}
+
+impl CommandTree {
+    pub const ROOT: usize = 0;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a child to `parent`, or returns the existing child that matches the same way. Brigadier
+    /// tells siblings apart by their name, so an argument that has the name of a different sibling is renamed.
+    pub fn add_child(&mut self, parent: usize, kind: NodeKind) -> usize {
+        if let Some(&existing) = self.nodes[parent]
+            .children
+            .iter()
+            .find(|&&child| self.nodes[child].kind == kind)
+        {
+            return existing;
+        }
+
+        let mut kind = kind;
+        if let NodeKind::Argument { name, .. } = &mut kind {
+            let base = name.clone();
+            let mut n = 1;
+            while self.nodes[parent]
+                .children
+                .iter()
+                .any(|&child| self.nodes[child].kind.name() == Some(name.as_str()))
+            {
+                n += 1;
+                *name = format!("{}_{}", base, n);
+            }
+        }
+
+        let id = self.nodes.len();
+        self.nodes.push(Node::new(kind));
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    /// Adds the nodes of `path` below the root, marks the last one executable, and returns it.
+    pub fn add_path(&mut self, path: &[NodeKind]) -> usize {
+        let mut node = Self::ROOT;
+        for kind in path {
+            node = self.add_child(node, kind.clone());
+        }
+        if node != Self::ROOT {
+            self.nodes[node].executable = true;
+        }
+        node
+    }
+
+    /// Writes the body of the Declare Commands packet: the nodes followed by the index of the root.
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        write_var_int(buf, self.nodes.len() as i32);
+        for node in &self.nodes {
+            let mut flags = match node.kind {
+                NodeKind::Root => 0,
+                NodeKind::Literal(_) => 1,
+                NodeKind::Argument { .. } => 2,
+            };
+            if node.executable {
+                flags |= 0x04;
+            }
+            if node.redirect.is_some() {
+                flags |= 0x08;
+            }
+            let suggestions = match &node.kind {
+                NodeKind::Argument { suggestions, .. } => suggestions.as_deref(),
+                _ => None,
+            };
+            if suggestions.is_some() {
+                flags |= 0x10;
+            }
+            buf.push(flags);
+
+            write_var_int(buf, node.children.len() as i32);
+            for child in &node.children {
+                write_var_int(buf, *child as i32);
+            }
+            if let Some(redirect) = node.redirect {
+                write_var_int(buf, redirect as i32);
+            }
+            match &node.kind {
+                NodeKind::Root => {}
+                NodeKind::Literal(name) => write_string(buf, name),
+                NodeKind::Argument { name, parser, .. } => {
+                    write_string(buf, name);
+                    write_string(buf, parser.identifier());
+                    parser.write_properties(buf);
+                }
+            }
+            if let Some(suggestions) = suggestions {
+                write_string(buf, suggestions);
+            }
+        }
+        write_var_int(buf, Self::ROOT as i32);
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write(&mut buf);
+        buf
+    }
+}
+
+fn write_var_int(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7f == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+}
+
+fn write_string(buf: &mut Vec<u8>, value: &str) {
+    write_var_int(buf, value.len() as i32);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_var_int, ArgumentType, CommandTree, NodeKind, StringKind, ASK_SERVER};
+
+    #[test]
+    fn var_int() {
+        let encode = |value| {
+            let mut buf = Vec::new();
+            write_var_int(&mut buf, value);
+            buf
+        };
+        assert_eq!(encode(0), vec![0x00]);
+        assert_eq!(encode(300), vec![0xac, 0x02]);
+        assert_eq!(encode(-1), vec![0xff, 0xff, 0xff, 0xff, 0x0f]);
+    }
+
+    #[test]
+    fn tree() {
+        let mut tree = CommandTree::new();
+        let time = NodeKind::Literal(String::from("time"));
+        let amount = NodeKind::argument(
+            "time",
+            ArgumentType::Integer {
+                min: Some(0),
+                max: None,
+            },
+        );
+        tree.add_path(&[
+            time.clone(),
+            NodeKind::Literal(String::from("set")),
+            amount.clone(),
+        ]);
+        tree.add_path(&[time.clone(), NodeKind::Literal(String::from("add")), amount]);
+        tree.add_path(&[time, NodeKind::argument("add", ArgumentType::Time)]);
+
+        assert_eq!(tree.nodes.len(), 7);
+        assert_eq!(tree.nodes[1].children, vec![2, 4, 6]);
+        assert_eq!(tree.nodes[6].kind.name(), Some("add_2"));
+        assert!(tree.nodes[3].executable && !tree.nodes[2].executable);
+
+        let bytes = tree.to_bytes();
+        // Root, then the literal "time" with its three children.
+        assert_eq!(&bytes[..6], &[7, 0x00, 1, 1, 0x01, 3]);
+        // The `set` node, followed by the integer argument with a minimum of 0.
+        let mut argument = vec![0x06, 0, 4];
+        argument.extend(b"time\x11brigadier:integer\x01\x00\x00\x00\x00");
+        assert!(bytes
+            .windows(argument.len())
+            .any(|w| w == argument.as_slice()));
+        assert_eq!(bytes.last(), Some(&0));
+    }
+
+    #[test]
+    fn suggestions() {
+        let mut tree = CommandTree::new();
+        tree.add_path(&[
+            NodeKind::Literal(String::from("wait")),
+            NodeKind::argument("time", ArgumentType::String(StringKind::SingleWord))
+                .with_suggestions(ASK_SERVER),
+        ]);
+
+        let bytes = tree.to_bytes();
+        // An executable argument with suggestions, and the suggestions after its properties.
+        let mut argument = vec![0x16, 0];
+        argument.extend(b"\x04time\x10brigadier:string\x00\x14minecraft:ask_server");
+        assert!(bytes
+            .windows(argument.len())
+            .any(|w| w == argument.as_slice()));
+    }
+}
@@ -1,4 +1,6 @@
-use super::CommandSpec;
+use std::future::Future;
+
+use super::{AsyncCommandSpec, CommandSpec, RedirectSpec};
 use crate::{
     argument::Argument,
     generic::Func,
@@ -7,13 +9,11 @@ use crate::{
 
 // use std::marker::PhantomData;
 pub fn space() -> OneOrMoreSpace {
-    OneOrMoreSpace
+    OneOrMoreSpace::new()
 }
 
 pub fn literal(value: &str) -> parser::Literal {
-    parser::Literal {
-        value: String::from(value),
-    }
+    parser::Literal::new(String::from(value))
 }
 
 pub trait CommandBuilder {
@@ -21,13 +21,41 @@ pub trait CommandBuilder {
     fn arg<A: Argument>(self) -> And<Self::Parser, <A as Argument>::Parser>;
     fn space(self) -> And<Self::Parser, OneOrMoreSpace>;
     fn followed_by<P: IterParser>(self, parser: P) -> And<Self::Parser, P>;
+    /// Finishes the command with a handler that takes the extracted arguments, up to 64 of them. Every
+    /// argument nests the parser type deeper, so a crate with commands of more than about 30 arguments
+    /// has to raise its `#![recursion_limit]`, to 512 for 64 arguments. The command may end with
+    /// separators, unless it is registered with [`Separator::Space`](crate::parser::Separator::Space).
     fn on_call<GameState, CommandResult, F1, F2>(
         self,
         f: F1,
     ) -> CommandSpec<GameState, CommandResult, F1, F2, And<Self::Parser, MaybeSpaces>>
+    where
+        F1: Func<<Self::Parser as IterParser>::Extract, Output = F2>;
+    fn on_call_async<GameState, Fut, F1, F2>(
+        self,
+        f: F1,
+    ) -> AsyncCommandSpec<GameState, Fut, F1, F2, And<Self::Parser, MaybeSpaces>>
     where
         F1: Func<<Self::Parser as IterParser>::Extract, Output = F2>,
-        F2: Func<GameState, Output = CommandResult>;
+        F2: Func<GameState, Output = Fut>,
+        Fut: Future;
+    /// Turns the parser into the start of a [`Redirect`](super::Redirect). The handler returns the
+    /// sources to continue with, and the rest of the input is appended to `target` and dispatched again.
+    fn on_redirect<GameState, F1, F2>(
+        self,
+        target: &str,
+        f: F1,
+    ) -> RedirectSpec<GameState, F1, F2, Self::Parser>
+    where
+        F1: Func<<Self::Parser as IterParser>::Extract, Output = F2>;
+    /// Like [`CommandBuilder::on_redirect`], but the sources count as forks of the source.
+    fn on_fork<GameState, F1, F2>(
+        self,
+        target: &str,
+        f: F1,
+    ) -> RedirectSpec<GameState, F1, F2, Self::Parser>
+    where
+        F1: Func<<Self::Parser as IterParser>::Extract, Output = F2>;
 }
 
 impl<T> CommandBuilder for T
@@ -57,21 +85,77 @@ where
     ) -> CommandSpec<GameState, CommandResult, F1, F2, And<Self::Parser, MaybeSpaces>>
     where
         F1: Func<<Self::Parser as IterParser>::Extract, Output = F2>,
-        F2: Func<GameState, Output = CommandResult>,
     {
         CommandSpec {
-            parser: self.followed_by(MaybeSpaces),
+            parser: self.followed_by(MaybeSpaces::trailing()),
             mapping: f,
             gamestate: Default::default(),
             command_result: Default::default(),
             mapping_result: Default::default(),
         }
     }
+
+    fn on_call_async<GameState, Fut, F1, F2>(
+        self,
+        f: F1,
+    ) -> AsyncCommandSpec<GameState, Fut, F1, F2, And<Self::Parser, MaybeSpaces>>
+    where
+        F1: Func<<Self::Parser as IterParser>::Extract, Output = F2>,
+        F2: Func<GameState, Output = Fut>,
+        Fut: Future,
+    {
+        AsyncCommandSpec {
+            spec: self.on_call(f),
+        }
+    }
+
+    fn on_redirect<GameState, F1, F2>(
+        self,
+        target: &str,
+        f: F1,
+    ) -> RedirectSpec<GameState, F1, F2, Self::Parser>
+    where
+        F1: Func<<Self::Parser as IterParser>::Extract, Output = F2>,
+    {
+        RedirectSpec {
+            parser: self,
+            target: String::from(target),
+            forks: false,
+            separator: Default::default(),
+            mapping: f,
+            gamestate: Default::default(),
+            mapping_result: Default::default(),
+        }
+    }
+
+    fn on_fork<GameState, F1, F2>(
+        self,
+        target: &str,
+        f: F1,
+    ) -> RedirectSpec<GameState, F1, F2, Self::Parser>
+    where
+        F1: Func<<Self::Parser as IterParser>::Extract, Output = F2>,
+    {
+        RedirectSpec {
+            forks: true,
+            ..self.on_redirect(target, f)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::command::{Command, CommandSpec};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    };
+
+    use std::rc::Rc;
+
+    use crate::command::{
+        AsyncCommand, AsyncCommandSpec, BoxedCommand, Command, CommandSpec, LocalBoxedCommand,
+    };
 
     use super::{literal, CommandBuilder};
 
@@ -93,4 +177,208 @@ mod tests {
         assert!(cmd.call((x, y), "/echo 10").is_ok());
         println!("{:?}", cmd.call((x, y), "/echo 10 "));
     }
+
+    fn noop_waker() -> Waker {
+        fn clone(_: *const ()) -> RawWaker {
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+        // Safety: the vtable ignores the data pointer, so a null pointer is fine.
+        unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = Pin::as_mut(&mut future).poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn call_async() {
+        let cmd: AsyncCommandSpec<(&mut u32,), _, _, _, _> = literal("/wait")
+            .space()
+            .arg::<u32>()
+            .on_call_async(|ticks: u32| {
+                move |waited: &mut u32| {
+                    *waited += ticks;
+                    async move { ticks * 2 }
+                }
+            });
+
+        let waited = &mut 0;
+        let future = cmd.call((waited,), "/wait 20").unwrap();
+        assert_eq!(*waited, 20);
+        assert_eq!(block_on(future), 40);
+
+        assert!(cmd.call((waited,), "/wat 20").is_err());
+        assert_eq!(*waited, 20);
+
+        let suggestions = cmd.suggest("/wa");
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].text, "/wait");
+        assert_eq!(cmd.brigadier().len(), 1);
+    }
+
+    #[test]
+    fn boxed() {
+        let commands: Vec<BoxedCommand<(Rc<u32>,), u32>> = vec![
+            literal("/add")
+                .space()
+                .arg::<u32>()
+                .on_call(|n: u32| move |x: Rc<u32>| *x + n)
+                .boxed(),
+            literal("/double").on_call(|| |x: Rc<u32>| *x * 2).boxed(),
+        ];
+
+        let results: Vec<u32> = commands
+            .iter()
+            .filter_map(|cmd| cmd.call((Rc::new(10),), "/double").ok())
+            .collect();
+        assert_eq!(results, vec![20]);
+        assert_eq!(commands[0].call((Rc::new(10),), "/add 5").unwrap(), 15);
+    }
+
+    #[test]
+    fn boxed_local() {
+        let offset = Rc::new(7);
+        let commands: Vec<LocalBoxedCommand<(), u32>> = vec![
+            literal("/offset")
+                .on_call(move || {
+                    let offset = offset.clone();
+                    move || *offset
+                })
+                .boxed_local(),
+            literal("/zero").on_call(|| || 0).boxed_local(),
+        ];
+
+        assert_eq!(commands[0].call((), "/offset").unwrap(), 7);
+        assert_eq!(commands[1].call((), "/zero").unwrap(), 0);
+        assert!(commands[1].call((), "/offset").is_err());
+    }
+
+    #[test]
+    fn many_arguments() {
+        let cmd = literal("/fill")
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .space()
+            .arg::<u32>()
+            .on_call(
+                |a0: u32,
+                 a1: u32,
+                 a2: u32,
+                 a3: u32,
+                 a4: u32,
+                 a5: u32,
+                 a6: u32,
+                 a7: u32,
+                 a8: u32,
+                 a9: u32,
+                 a10: u32,
+                 a11: u32,
+                 a12: u32,
+                 a13: u32,
+                 a14: u32,
+                 a15: u32,
+                 a16: u32,
+                 a17: u32,
+                 a18: u32,
+                 a19: u32| {
+                    move || {
+                        [
+                            a0, a1, a2, a3, a4, a5, a6, a7, a8, a9, a10, a11, a12, a13, a14, a15,
+                            a16, a17, a18, a19,
+                        ]
+                        .iter()
+                        .sum::<u32>()
+                    }
+                },
+            );
+
+        let input = format!(
+            "/fill {}",
+            (1..=20)
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        assert_eq!(cmd.call((), &input).unwrap(), 210);
+    }
+
+    // The most arguments a handler can take, one for every generated Func impl.
+    #[test]
+    fn max_arguments() {
+        macro_rules! number {
+            ($arg:ident) => {
+                u32
+            };
+        }
+        macro_rules! sum_command {
+            ($($arg:ident),*) => {
+                literal("/sum")
+                    $(.space().arg::<number!($arg)>())*
+                    .on_call(|$($arg: u32),*| move || 0 $(+ $arg)*)
+            };
+        }
+
+        let cmd = sum_command!(
+            a0, a1, a2, a3, a4, a5, a6, a7, a8, a9, a10, a11, a12, a13, a14, a15, a16, a17, a18,
+            a19, a20, a21, a22, a23, a24, a25, a26, a27, a28, a29, a30, a31, a32, a33, a34, a35,
+            a36, a37, a38, a39, a40, a41, a42, a43, a44, a45, a46, a47, a48, a49, a50, a51, a52,
+            a53, a54, a55, a56, a57, a58, a59, a60, a61, a62, a63
+        );
+
+        let input = format!(
+            "/sum {}",
+            (1..=64)
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        assert_eq!(cmd.call((), &input).unwrap(), 2080);
+        assert!(cmd.call((), "/sum 1 2 3").is_err());
+    }
 }
@@ -1,10 +1,19 @@
 pub mod builder;
+mod outcome;
+mod redirect;
 
-use std::marker::PhantomData;
+use std::{future::Future, marker::PhantomData};
 
-use anyhow::bail;
+use crate::{
+    argument::Registries,
+    brigadier::NodeKind,
+    feedback::Message,
+    generic::Func,
+    parser::{IterParser, Located, Separator, Suggestion},
+};
 
-use crate::{generic::Func, parser::IterParser};
+pub use outcome::*;
+pub use redirect::*;
 
 #[derive(Clone, Copy, Default, PartialEq, Eq, std::hash::Hash, Debug)]
 pub struct CommandId {
@@ -22,14 +31,136 @@ pub trait Command {
     type CommandResult;
     fn call(&self, gamestate: Self::GameState, input: &str) -> anyhow::Result<Self::CommandResult>;
     fn regex(&self) -> String;
+
+    /// Returns ways to complete the input, see [`IterParser::suggest`].
+    fn suggest(&self, _input: &str) -> Vec<Suggestion> {
+        Vec::new()
+    }
+
+    /// Describes the command as brigadier nodes, see [`IterParser::brigadier`].
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        Vec::new()
+    }
+
+    /// Makes the command separate words with `separator`, see [`IterParser::set_separator`].
+    fn set_separator(&mut self, _separator: &Separator) {}
+
+    /// Erases the concrete parser and handler types, so the command can be stored next to others.
+    fn boxed(self) -> BoxedCommand<Self::GameState, Self::CommandResult>
+    where
+        Self: Sized + Send + Sync + 'static,
+    {
+        Box::new(self)
+    }
+
+    /// Like [`Command::boxed`], for commands that are not `Send + Sync`, like handlers that capture an `Rc`.
+    fn boxed_local(self) -> LocalBoxedCommand<Self::GameState, Self::CommandResult>
+    where
+        Self: Sized + 'static,
+    {
+        Box::new(self)
+    }
+}
+
+/// A [`Command`] with its parser and handler types erased.
+pub type DynCommand<GameState, CommandResult> =
+    dyn Command<GameState = GameState, CommandResult = CommandResult> + Send + Sync;
+
+pub type BoxedCommand<GameState, CommandResult> = Box<DynCommand<GameState, CommandResult>>;
+
+/// A [`DynCommand`] that can only be used on the thread that made it.
+pub type LocalDynCommand<GameState, CommandResult> =
+    dyn Command<GameState = GameState, CommandResult = CommandResult>;
+
+pub type LocalBoxedCommand<GameState, CommandResult> =
+    Box<LocalDynCommand<GameState, CommandResult>>;
+
+impl<C: Command + ?Sized> Command for Box<C> {
+    type GameState = C::GameState;
+    type CommandResult = C::CommandResult;
+
+    fn call(&self, gamestate: Self::GameState, input: &str) -> anyhow::Result<Self::CommandResult> {
+        (**self).call(gamestate, input)
+    }
+
+    fn regex(&self) -> String {
+        (**self).regex()
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        (**self).suggest(input)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        (**self).brigadier()
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        (**self).set_separator(separator)
+    }
+}
+
+/// The error that got furthest into the input over all matches a parser tried. The first error wins a tie.
+#[derive(Default)]
+pub(crate) struct Furthest {
+    error: Option<(usize, anyhow::Error)>,
+}
+
+impl Furthest {
+    pub(crate) fn add(&mut self, error: anyhow::Error) {
+        let (position, error) = Located::split(error);
+        match &self.error {
+            Some((furthest, _)) if *furthest >= position => {}
+            _ => self.error = Some((position, error)),
+        }
+    }
+
+    /// The furthest error, or `command.unknown.argument` if the parser never reported one.
+    pub(crate) fn into_error(self) -> anyhow::Error {
+        Located::split(self.into_located()).1
+    }
+
+    /// Like [`Furthest::into_error`], but the error stays [`Located`], so it can be compared with the
+    /// errors of other commands.
+    pub(crate) fn into_located(self) -> anyhow::Error {
+        match self.error {
+            Some((position, error)) => anyhow::Error::new(Located { position, error }),
+            None => anyhow::Error::new(Message::translatable("command.unknown.argument", vec![])),
+        }
+    }
 }
 
 pub struct CommandSpec<GameState, CommandResult, F1, F2, P> {
     pub(crate) parser: P,
     pub(crate) mapping: F1,
-    pub(crate) gamestate: PhantomData<GameState>,
-    pub(crate) command_result: PhantomData<CommandResult>,
-    pub(crate) mapping_result: PhantomData<F2>,
+    // Function pointers keep the spec Send + Sync regardless of what the game state holds.
+    pub(crate) gamestate: PhantomData<fn() -> GameState>,
+    pub(crate) command_result: PhantomData<fn() -> CommandResult>,
+    pub(crate) mapping_result: PhantomData<fn() -> F2>,
+}
+
+impl<CommandResult, P: IterParser, GameState, F1, F2, Ext>
+    CommandSpec<GameState, CommandResult, F1, F2, P>
+where
+    F1: Func<Ext, Output = F2>,
+    P: IterParser<Extract = Ext>,
+{
+    /// Runs the parser until it has a match that consumes the whole input, and hands the extract to the mapping.
+    /// If there is none, the error that got furthest into the input is returned, [`Located`] where it happened.
+    fn parse(&self, registries: &dyn Registries, input: &str) -> anyhow::Result<F2> {
+        let mut furthest = Furthest::default();
+        let mut state = Some(P::ParserState::default());
+        while let Some(current) = state {
+            let (result, next) = self.parser.parse_in(registries, current, input);
+            match result {
+                Ok((ext, "")) => return Ok(self.mapping.call(ext)),
+                Ok(_) => {}
+                Err(err) => furthest.add(err),
+            }
+            state = next;
+        }
+        Err(furthest.into_located())
+    }
 }
 
 impl<CommandResult, P: IterParser, GameState, F1, F2, Ext> Command
@@ -47,15 +178,228 @@ where
     }
 
     fn call(&self, gamestate: GameState, input: &str) -> anyhow::Result<CommandResult> {
-        let mut state = P::ParserState::default();
-        loop {
-            match self.parser.parse(state, input) {
-                (Ok((ext, _)), _) => return Ok(self.mapping.call(ext).call(gamestate)),
-                (Err(_), None) => {
-                    bail!("Not able to parse input");
-                }
-                (Err(_), Some(next_state)) => state = next_state,
-            }
-        }
+        let handler = self
+            .parse(&(), input)
+            .map_err(|error| Located::split(error).1)?;
+        Ok(handler.call(gamestate))
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        self.parser.suggest(input)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        self.parser.brigadier()
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.parser.set_separator(separator)
+    }
+}
+
+/// A game state that borrows from the game, like `(&'a mut World,)`, described for every lifetime `'a` at once.
+/// Storing commands by their family keeps the borrow lifetime out of the type of the registry.
+pub trait GameStateFamily {
+    type GameState<'a>;
+
+    /// Shortens the borrows of a game state, so the same state can be handed out more than once.
+    fn reborrow<'b>(gamestate: &'b mut Self::GameState<'_>) -> Self::GameState<'b>;
+
+    /// The registries of the game, which the arguments of a command are checked against, see [`Registries`].
+    /// By default there are none, and any well formed id is accepted.
+    fn registries<'b>(_gamestate: &'b Self::GameState<'_>) -> &'b dyn Registries {
+        &()
+    }
+}
+
+impl GameStateFamily for () {
+    type GameState<'a> = ();
+
+    fn reborrow<'b>(_gamestate: &'b mut Self::GameState<'_>) -> Self::GameState<'b> {}
+}
+
+/// The family of game states `(&'a mut T,)`.
+pub struct RefMut<T>(PhantomData<fn() -> T>);
+
+impl<T: 'static> GameStateFamily for RefMut<T> {
+    type GameState<'a> = (&'a mut T,);
+
+    fn reborrow<'b>(gamestate: &'b mut Self::GameState<'_>) -> Self::GameState<'b> {
+        (&mut *gamestate.0,)
+    }
+}
+
+/// A command that accepts the game state of a [`GameStateFamily`] with any lifetime.
+pub trait FamilyCommand<G: GameStateFamily> {
+    type CommandResult;
+
+    /// Parses the input and calls the handler. A parse error may remember how far into the input it
+    /// happened, which the dispatcher uses to report the error of the command that got furthest.
+    /// [`Message::from`] and the display of the error see through this.
+    fn call(&self, gamestate: G::GameState<'_>, input: &str)
+        -> anyhow::Result<Self::CommandResult>;
+    fn regex(&self) -> String;
+
+    /// Returns ways to complete the input, see [`IterParser::suggest`].
+    fn suggest(&self, _input: &str) -> Vec<Suggestion> {
+        Vec::new()
+    }
+
+    /// Like [`FamilyCommand::suggest`], with the registries of the game, see [`IterParser::suggest_in`].
+    fn suggest_in(&self, _registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        self.suggest(input)
+    }
+
+    /// Describes the command as brigadier nodes, see [`IterParser::brigadier`].
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        Vec::new()
+    }
+
+    /// Makes the command separate words with `separator`, see [`IterParser::set_separator`].
+    fn set_separator(&mut self, _separator: &Separator) {}
+}
+
+pub type BoxedFamilyCommand<G, CommandResult> =
+    Box<dyn FamilyCommand<G, CommandResult = CommandResult> + Send + Sync>;
+
+impl<G: GameStateFamily, C: FamilyCommand<G> + ?Sized> FamilyCommand<G> for Box<C> {
+    type CommandResult = C::CommandResult;
+
+    fn call(
+        &self,
+        gamestate: G::GameState<'_>,
+        input: &str,
+    ) -> anyhow::Result<Self::CommandResult> {
+        (**self).call(gamestate, input)
+    }
+
+    fn regex(&self) -> String {
+        (**self).regex()
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        (**self).suggest(input)
+    }
+
+    fn suggest_in(&self, registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        (**self).suggest_in(registries, input)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        (**self).brigadier()
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        (**self).set_separator(separator)
+    }
+}
+
+impl<G, CommandResult, P: IterParser, F1, F2, Ext> FamilyCommand<G>
+    for CommandSpec<G, CommandResult, F1, F2, P>
+where
+    G: GameStateFamily,
+    F1: Func<Ext, Output = F2>,
+    F2: for<'a> Func<G::GameState<'a>, Output = CommandResult>,
+    P: IterParser<Extract = Ext>,
+{
+    type CommandResult = CommandResult;
+
+    fn regex(&self) -> String {
+        self.parser.regex()
+    }
+
+    fn call(&self, gamestate: G::GameState<'_>, input: &str) -> anyhow::Result<CommandResult> {
+        Ok(self
+            .parse(G::registries(&gamestate), input)?
+            .call(gamestate))
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        self.parser.suggest(input)
+    }
+
+    fn suggest_in(&self, registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        self.parser.suggest_in(registries, input)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        self.parser.brigadier()
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.parser.set_separator(separator)
+    }
+}
+
+/// A type that can be parsed from a command on its own, usually through `#[derive(Command)]`.
+/// The derive collects the fields in nested pairs instead of one tuple, so the number of fields is not
+/// limited by the 64 arguments a handler can take. Long commands still nest deeply, so they need a
+/// higher `#![recursion_limit]`, see [`CommandBuilder::on_call`](builder::CommandBuilder::on_call).
+pub trait FromCommand: Sized {
+    type Parser: IterParser<Extract = (Self,)>;
+    fn parser() -> Self::Parser;
+}
+
+/// The asynchronous counterpart to [`Command`]. Parsing happens synchronously inside `call`,
+/// so the returned future only owns the extracted arguments and never borrows the input.
+///
+/// The [`Dispatcher`](crate::dispatcher::Dispatcher) only stores [`FamilyCommand`]s, so async commands are called
+/// directly. To dispatch one, register a command built with
+/// [`CommandBuilder::on_call`](builder::CommandBuilder::on_call) whose handler returns a boxed future.
+pub trait AsyncCommand {
+    type GameState;
+    type CommandResult;
+    type Future: Future<Output = Self::CommandResult>;
+    fn call(&self, gamestate: Self::GameState, input: &str) -> anyhow::Result<Self::Future>;
+    fn regex(&self) -> String;
+
+    /// Returns ways to complete the input, see [`IterParser::suggest`].
+    fn suggest(&self, _input: &str) -> Vec<Suggestion> {
+        Vec::new()
+    }
+
+    /// Describes the command as brigadier nodes, see [`IterParser::brigadier`].
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        Vec::new()
+    }
+
+    /// Makes the command separate words with `separator`, see [`IterParser::set_separator`].
+    fn set_separator(&mut self, _separator: &Separator) {}
+}
+
+pub struct AsyncCommandSpec<GameState, Fut, F1, F2, P> {
+    pub(crate) spec: CommandSpec<GameState, Fut, F1, F2, P>,
+}
+
+impl<Fut, P: IterParser, GameState, F1, F2, Ext> AsyncCommand
+    for AsyncCommandSpec<GameState, Fut, F1, F2, P>
+where
+    F1: Func<Ext, Output = F2>,
+    F2: Func<GameState, Output = Fut>,
+    P: IterParser<Extract = Ext>,
+    Fut: Future,
+{
+    type GameState = GameState;
+    type CommandResult = Fut::Output;
+    type Future = Fut;
+
+    fn regex(&self) -> String {
+        Command::regex(&self.spec)
+    }
+
+    fn call(&self, gamestate: GameState, input: &str) -> anyhow::Result<Fut> {
+        Command::call(&self.spec, gamestate, input)
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        Command::suggest(&self.spec, input)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        Command::brigadier(&self.spec)
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        Command::set_separator(&mut self.spec, separator)
     }
 }
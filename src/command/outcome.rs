@@ -0,0 +1,109 @@
+use std::iter::Sum;
+
+/// What a command reports back to command blocks and `/execute store`: how often it succeeded, and the
+/// number it produced, like the amount of items `/clear` removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CommandOutcome {
+    pub success: u32,
+    pub result: i32,
+}
+
+impl CommandOutcome {
+    pub const FAILURE: Self = Self {
+        success: 0,
+        result: 0,
+    };
+
+    pub fn new(success: u32, result: i32) -> Self {
+        Self { success, result }
+    }
+
+    /// A single success with the given result.
+    pub fn success(result: i32) -> Self {
+        Self { success: 1, result }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.success > 0
+    }
+
+    /// A failed command has neither successes nor a result.
+    pub fn from_result<R: Into<CommandOutcome>>(result: anyhow::Result<R>) -> Self {
+        result.map_or(Self::FAILURE, Into::into)
+    }
+
+    /// Adds up the successes and results of two commands that ran one after another.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            success: self.success.saturating_add(other.success),
+            result: self.result.saturating_add(other.result),
+        }
+    }
+
+    /// Combines the outcomes of a command that was forked into several sources. Like brigadier, both
+    /// the success count and the result are the number of forks that succeeded.
+    pub fn forked<I: IntoIterator<Item = Self>>(outcomes: I) -> Self {
+        let successes = outcomes
+            .into_iter()
+            .filter(CommandOutcome::is_success)
+            .count();
+        Self {
+            success: successes as u32,
+            result: successes as i32,
+        }
+    }
+}
+
+impl Sum for CommandOutcome {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::FAILURE, Self::merge)
+    }
+}
+
+/// Commands without a result succeed once with a result of 1, like brigadier's `SINGLE_SUCCESS`.
+impl From<()> for CommandOutcome {
+    fn from(_: ()) -> Self {
+        Self::success(1)
+    }
+}
+
+impl From<i32> for CommandOutcome {
+    fn from(result: i32) -> Self {
+        Self::success(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::CommandOutcome;
+
+    #[test]
+    fn aggregate() {
+        assert_eq!(CommandOutcome::from(()), CommandOutcome::new(1, 1));
+        assert_eq!(
+            CommandOutcome::from_result::<i32>(Err(anyhow!("no"))),
+            CommandOutcome::FAILURE
+        );
+        assert_eq!(
+            CommandOutcome::from_result(Ok(7)),
+            CommandOutcome::new(1, 7)
+        );
+
+        let outcomes = vec![
+            CommandOutcome::success(5),
+            CommandOutcome::FAILURE,
+            CommandOutcome::success(-2),
+        ];
+        assert_eq!(
+            outcomes.iter().copied().sum::<CommandOutcome>(),
+            CommandOutcome::new(2, 3)
+        );
+        assert_eq!(CommandOutcome::forked(outcomes), CommandOutcome::new(2, 2));
+        assert_eq!(
+            CommandOutcome::success(i32::MAX).merge(CommandOutcome::success(1)),
+            CommandOutcome::new(2, i32::MAX)
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::marker::PhantomData;
+
+use anyhow::Result;
+
+use crate::{
+    argument::Registries,
+    brigadier::NodeKind,
+    generic::Func,
+    parser::{IterParser, Separator, Suggestion},
+    source::CommandSource,
+};
+
+use super::{Furthest, GameStateFamily};
+
+/// The start of a command that changes the source and then continues with more input, like
+/// `/execute as <targets>` or `/execute run`. The dispatcher calls the rest once for every source.
+pub trait Redirect<G: GameStateFamily> {
+    /// Parses the start of the input and returns the sources to continue with, together with the
+    /// input to dispatch for each of them.
+    fn fork(
+        &self,
+        gamestate: G::GameState<'_>,
+        input: &str,
+    ) -> Result<(Vec<CommandSource>, String)>;
+    fn regex(&self) -> String;
+
+    /// Parses the start of the input like [`Redirect::fork`], without calling the handler, and returns the
+    /// rest of the input. The dispatcher uses it to suggest completions for what follows the redirect.
+    /// Redirects that return `None` are not followed.
+    fn rest<'i>(&self, _registries: &dyn Registries, _input: &'i str) -> Option<&'i str> {
+        None
+    }
+
+    /// Returns ways to complete the start of the input, see [`IterParser::suggest`].
+    fn suggest(&self, _input: &str) -> Vec<Suggestion> {
+        Vec::new()
+    }
+
+    /// Like [`Redirect::suggest`], with the registries of the game, see [`IterParser::suggest_in`].
+    fn suggest_in(&self, _registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        self.suggest(input)
+    }
+
+    /// Describes the start of the input as brigadier nodes, see [`IterParser::brigadier`].
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        Vec::new()
+    }
+
+    /// Where parsing continues, as the input the rest is appended to.
+    fn target(&self) -> &str;
+
+    /// If the redirect forks the source, like `/execute as`, rather than only changing it like
+    /// `/execute run`. The result of a forked command is the number of forks that succeeded.
+    fn forks(&self) -> bool;
+
+    /// Makes the redirect separate words with `separator`, see [`IterParser::set_separator`].
+    fn set_separator(&mut self, _separator: &Separator) {}
+}
+
+pub type BoxedRedirect<G> = Box<dyn Redirect<G> + Send + Sync>;
+
+/// A [`Redirect`] built from a parser and a handler that returns the new sources.
+pub struct RedirectSpec<GameState, F1, F2, P> {
+    pub(crate) parser: P,
+    pub(crate) target: String,
+    pub(crate) forks: bool,
+    pub(crate) separator: Separator,
+    pub(crate) mapping: F1,
+    pub(crate) gamestate: PhantomData<fn() -> GameState>,
+    pub(crate) mapping_result: PhantomData<fn() -> F2>,
+}
+
+impl<GameState, P, F1, F2, Ext> RedirectSpec<GameState, F1, F2, P>
+where
+    F1: Func<Ext, Output = F2>,
+    P: IterParser<Extract = Ext>,
+{
+    /// Runs the parser until it has a match that ends at a separator or the end of the input, and
+    /// returns the mapped extract with the rest of the input. If there is none, the error that got
+    /// furthest into the input is returned.
+    fn parse<'i>(&self, registries: &dyn Registries, input: &'i str) -> Result<(F2, &'i str)> {
+        let mut furthest = Furthest::default();
+        let mut state = Some(P::ParserState::default());
+        while let Some(current) = state {
+            let (result, next) = self.parser.parse_in(registries, current, input);
+            match result {
+                Ok((ext, out)) if self.separator.is_boundary(out) => {
+                    return Ok((self.mapping.call(ext), self.separator.skip(out)))
+                }
+                Ok(_) => {}
+                Err(err) => furthest.add(err),
+            }
+            state = next;
+        }
+        Err(furthest.into_error())
+    }
+}
+
+impl<G, P, F1, F2, Ext> Redirect<G> for RedirectSpec<G, F1, F2, P>
+where
+    G: GameStateFamily,
+    F1: Func<Ext, Output = F2>,
+    F2: for<'a> Func<G::GameState<'a>, Output = Vec<CommandSource>>,
+    P: IterParser<Extract = Ext>,
+{
+    fn fork(
+        &self,
+        gamestate: G::GameState<'_>,
+        input: &str,
+    ) -> Result<(Vec<CommandSource>, String)> {
+        let (handler, rest) = self.parse(G::registries(&gamestate), input)?;
+        Ok((handler.call(gamestate), format!("{}{}", self.target, rest)))
+    }
+
+    fn regex(&self) -> String {
+        format!("{}({}.*)?", self.parser.regex(), self.separator.regex())
+    }
+
+    fn rest<'i>(&self, registries: &dyn Registries, input: &'i str) -> Option<&'i str> {
+        self.parse(registries, input).ok().map(|(_, rest)| rest)
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        self.parser.suggest(input)
+    }
+
+    fn suggest_in(&self, registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        self.parser.suggest_in(registries, input)
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        self.parser.brigadier()
+    }
+
+    fn target(&self) -> &str {
+        &self.target
+    }
+
+    fn forks(&self) -> bool {
+        self.forks
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.parser.set_separator(separator);
+        self.separator = separator.clone();
+    }
+}
@@ -0,0 +1,1174 @@
+use std::{fmt, sync::OnceLock};
+
+use anyhow::anyhow;
+
+use crate::{
+    argument::Registries,
+    brigadier::{CommandTree, NodeKind},
+    command::{
+        BoxedFamilyCommand, BoxedRedirect, CommandId, CommandOutcome, FamilyCommand, Furthest,
+        GameStateFamily, Redirect,
+    },
+    feedback::{FeedbackFamily, Message},
+    parser::{fold_case, Separator, Suggestion},
+    regex::{CmdPos, DFA, NFA},
+    source::{CommandSource, SourceFamily},
+};
+
+/// Keeps redirects that lead back to themselves from recursing forever.
+const MAX_REDIRECTS: usize = 64;
+
+const MAX_SUGGESTIONS: usize = 3;
+
+/// The literals that are closest to a mistyped word, attached to the error of [`Dispatcher::call`]
+/// when no command matches the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DidYouMean {
+    /// Where the word starts in the input, in bytes.
+    pub position: usize,
+    pub word: String,
+    /// The closest literals first.
+    pub suggestions: Vec<String>,
+}
+
+impl DidYouMean {
+    pub fn message(&self) -> Message {
+        Message::translatable(
+            "lieutenant.command.suggestion",
+            vec![Message::text(self.suggestions.join(", "))],
+        )
+    }
+}
+
+impl fmt::Display for DidYouMean {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for DidYouMean {}
+
+/// The number of insertions, deletions, substitutions and swaps of neighbouring characters that
+/// turn one word into the other, without editing any part twice.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    distances[0] = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// Stores commands and routes input to the right one.
+///
+/// The regex of every command is compiled into one early terminating DFA, which narrows the input
+/// down to a few candidate commands before any parser runs. Because the dispatcher is generic over a
+/// [`GameStateFamily`], it can be stored for the lifetime of the game and called with fresh borrows every tick.
+pub struct Dispatcher<G: GameStateFamily, CommandResult> {
+    separator: Separator,
+    commands: Vec<BoxedFamilyCommand<G, CommandResult>>,
+    automaton: Automaton,
+    redirects: Vec<BoxedRedirect<G>>,
+    redirect_automaton: Automaton,
+    /// The brigadier nodes of every command and redirect with the leading `/` kept, which is what
+    /// misspelled and abbreviated literals are looked up in.
+    literals: CommandTree,
+}
+
+/// The regexes of every command or every redirect of a dispatcher. Registering only extends the NFA,
+/// the DFA is built from it the first time it is needed, so registering many commands stays linear.
+#[derive(Default)]
+struct Automaton {
+    nfa: Option<NFA<CmdPos<CommandId>>>,
+    dfa: OnceLock<DFA<CmdPos<CommandId>>>,
+}
+
+impl Automaton {
+    fn add_regex(&mut self, regex: &str, id: CommandId) -> anyhow::Result<()> {
+        let command_nfa = NFA::<CmdPos<CommandId>>::from_command_regex(regex, id)?;
+        let combined = match self.nfa.take() {
+            Some(nfa) => nfa.or(command_nfa)?,
+            None => command_nfa,
+        };
+
+        self.nfa = Some(combined);
+        self.dfa = OnceLock::new();
+        Ok(())
+    }
+
+    fn dfa(&self) -> &DFA<CmdPos<CommandId>> {
+        self.dfa.get_or_init(|| match &self.nfa {
+            Some(nfa) => nfa.clone().into_early_termination_dfa(),
+            None => DFA::new(),
+        })
+    }
+
+    /// The ids of the regexes that could match the input, each once.
+    fn candidates(&self, input: &str) -> Vec<CommandId> {
+        let mut candidates = self.dfa().early_termination_find(input).unwrap_or_default();
+        candidates.sort_by_key(|id| id.id);
+        candidates.dedup();
+        candidates
+    }
+}
+
+/// Commands are usually registered with a leading `/`, which is not part of the command on the client.
+fn strip_slash(path: &mut Vec<NodeKind>) {
+    if let Some(NodeKind::Literal(first)) = path.first_mut() {
+        if let Some(stripped) = first.strip_prefix('/') {
+            *first = String::from(stripped);
+        }
+        if first.is_empty() {
+            path.remove(0);
+        }
+    }
+}
+
+impl<G: GameStateFamily, CommandResult> Default for Dispatcher<G, CommandResult> {
+    fn default() -> Self {
+        Self {
+            separator: Separator::default(),
+            commands: Vec::new(),
+            automaton: Automaton::default(),
+            redirects: Vec::new(),
+            redirect_automaton: Automaton::default(),
+            literals: CommandTree::new(),
+        }
+    }
+}
+
+impl<G: GameStateFamily, CommandResult> Dispatcher<G, CommandResult> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A dispatcher whose commands separate words with `separator` instead of any whitespace. Fails
+    /// if the separator has no characters, see [`Separator::validate`].
+    pub fn with_separator(separator: Separator) -> anyhow::Result<Self> {
+        separator.validate()?;
+        Ok(Self {
+            separator,
+            ..Self::default()
+        })
+    }
+
+    pub fn separator(&self) -> &Separator {
+        &self.separator
+    }
+
+    /// Adds a command to the dispatcher, and gives it the separator of the dispatcher. The DFA is
+    /// rebuilt on the next call, see [`Dispatcher::build`].
+    pub fn register<C>(&mut self, mut command: C) -> anyhow::Result<CommandId>
+    where
+        C: FamilyCommand<G, CommandResult = CommandResult> + Send + Sync + 'static,
+    {
+        command.set_separator(&self.separator);
+        let id = CommandId::of(self.commands.len());
+        self.automaton.add_regex(&command.regex(), id)?;
+        for path in command.brigadier() {
+            self.literals.add_path(&path);
+        }
+        self.commands.push(Box::new(command));
+        Ok(id)
+    }
+
+    /// Adds a redirect like `/execute as <targets>`, which [`Dispatcher::call_forked`] follows before
+    /// it calls a command.
+    pub fn register_redirect<R>(&mut self, mut redirect: R) -> anyhow::Result<()>
+    where
+        R: Redirect<G> + Send + Sync + 'static,
+    {
+        redirect.set_separator(&self.separator);
+        let id = CommandId::of(self.redirects.len());
+        self.redirect_automaton.add_regex(&redirect.regex(), id)?;
+        for path in redirect.brigadier() {
+            self.literals.add_path(&path);
+        }
+        self.redirects.push(Box::new(redirect));
+        Ok(())
+    }
+
+    /// Builds the DFAs of the registered commands and redirects now, instead of on the first call
+    /// after a command was registered.
+    pub fn build(&self) {
+        self.automaton.dfa();
+        self.redirect_automaton.dfa();
+    }
+
+    /// Calls the first registered command that is able to parse the input. If none is, the error of the
+    /// command that got furthest into the input is returned. If a word of the input is close to a literal
+    /// it was probably meant to be, the error has a [`DidYouMean`] as context.
+    pub fn call(
+        &self,
+        mut gamestate: G::GameState<'_>,
+        input: &str,
+    ) -> anyhow::Result<CommandResult> {
+        let candidates = self.automaton.candidates(input);
+        if candidates.len() > 1 {
+            if let Some(message) = self.ambiguous_prefix(input) {
+                return Err(anyhow!(message));
+            }
+        }
+
+        let error = if candidates.is_empty() {
+            anyhow!(Message::translatable("command.unknown.command", vec![]))
+        } else {
+            let mut furthest = Furthest::default();
+            for id in candidates {
+                match self.commands[id.id].call(G::reborrow(&mut gamestate), input) {
+                    Ok(result) => return Ok(result),
+                    Err(err) => furthest.add(err),
+                }
+            }
+            furthest.into_error()
+        };
+        Err(match self.did_you_mean(input) {
+            Some(did_you_mean) => error.context(did_you_mean),
+            None => error,
+        })
+    }
+
+    /// Finds a word of the input that is short for more than one literal, see [`LiteralMode::Prefix`](crate::parser::LiteralMode::Prefix).
+    /// Words that could also be an argument are never ambiguous.
+    fn ambiguous_prefix(&self, input: &str) -> Option<Message> {
+        let tree = &self.literals;
+        let mut node = CommandTree::ROOT;
+        for word in self.separator.words(input) {
+            let folded = fold_case(word);
+            let mut exact = None;
+            let mut argument = false;
+            let mut prefixed = Vec::new();
+            for &child in &tree.nodes[node].children {
+                match &tree.nodes[child].kind {
+                    NodeKind::Literal(literal) => {
+                        let literal_folded = fold_case(literal);
+                        if literal_folded == folded {
+                            exact = Some(child);
+                        } else if literal_folded.starts_with(&folded) {
+                            prefixed.push(literal.clone());
+                        }
+                    }
+                    NodeKind::Argument { .. } => argument = true,
+                    NodeKind::Root => {}
+                }
+            }
+
+            node = match exact {
+                Some(child) => child,
+                None if prefixed.len() > 1 && !argument => {
+                    return Some(Message::translatable(
+                        "lieutenant.command.ambiguous",
+                        vec![Message::text(word), Message::text(prefixed.join(", "))],
+                    ));
+                }
+                None => return None,
+            };
+        }
+        None
+    }
+
+    /// Finds the first word of the input that is not a literal of any command, and the literals that
+    /// could have been meant instead.
+    fn did_you_mean(&self, input: &str) -> Option<DidYouMean> {
+        let tree = &self.literals;
+        let mut node = CommandTree::ROOT;
+        for word in self.separator.words(input) {
+            let literals = tree.nodes[node].children.iter().filter_map(|&child| {
+                match &tree.nodes[child].kind {
+                    NodeKind::Literal(literal) => Some((child, literal)),
+                    _ => None,
+                }
+            });
+
+            let folded: Vec<char> = fold_case(word).chars().collect();
+            let mut matches = Vec::new();
+            let mut next = None;
+            for (child, literal) in literals {
+                let literal_folded: Vec<char> = fold_case(literal).chars().collect();
+                if literal_folded == folded {
+                    next = Some(child);
+                    break;
+                }
+                let distance = edit_distance(&folded, &literal_folded);
+                if distance <= (folded.len().max(literal_folded.len()) / 3).max(1) {
+                    matches.push((distance, literal.clone()));
+                }
+            }
+
+            node = match next {
+                Some(next) => next,
+                None if matches.is_empty() => return None,
+                None => {
+                    matches.sort();
+                    return Some(DidYouMean {
+                        position: word.as_ptr() as usize - input.as_ptr() as usize,
+                        word: String::from(word),
+                        suggestions: matches
+                            .into_iter()
+                            .take(MAX_SUGGESTIONS)
+                            .map(|(_, literal)| literal)
+                            .collect(),
+                    });
+                }
+            };
+        }
+        None
+    }
+
+    /// Follows the registered redirects at the start of the input, and calls the command at the end
+    /// once for every source they fork into. Returns each final source with the result of its command.
+    pub fn call_forked(
+        &self,
+        mut gamestate: G::GameState<'_>,
+        input: &str,
+    ) -> Vec<(CommandSource, anyhow::Result<CommandResult>)>
+    where
+        G: SourceFamily,
+    {
+        let source = G::source(&gamestate).clone();
+        let mut results = Vec::new();
+        self.fork(&mut gamestate, source, input, 0, &mut results);
+        results
+    }
+
+    /// Calls a command and reports its outcome. Failures have the outcome [`CommandOutcome::FAILURE`].
+    pub fn execute(&self, gamestate: G::GameState<'_>, input: &str) -> CommandOutcome
+    where
+        CommandResult: Into<CommandOutcome>,
+    {
+        CommandOutcome::from_result(self.call(gamestate, input))
+    }
+
+    /// Like [`Dispatcher::call_forked`], but combines the outcomes of every source into one. If a
+    /// redirect forked the source, the outcome counts the successful forks, see [`CommandOutcome::forked`].
+    pub fn execute_forked(&self, mut gamestate: G::GameState<'_>, input: &str) -> CommandOutcome
+    where
+        G: SourceFamily,
+        CommandResult: Into<CommandOutcome>,
+    {
+        let source = G::source(&gamestate).clone();
+        let mut results = Vec::new();
+        let forked = self.fork(&mut gamestate, source, input, 0, &mut results);
+        let outcomes = results
+            .into_iter()
+            .map(|(_, result)| CommandOutcome::from_result(result));
+        if forked {
+            CommandOutcome::forked(outcomes)
+        } else {
+            outcomes.sum()
+        }
+    }
+
+    /// Like [`Dispatcher::execute_forked`], and sends the error of every failed command to the feedback of
+    /// the game state. This way parse errors reach the executor through the same channel as the messages
+    /// of handlers.
+    pub fn run(&self, mut gamestate: G::GameState<'_>, input: &str) -> CommandOutcome
+    where
+        G: FeedbackFamily,
+        CommandResult: Into<CommandOutcome>,
+    {
+        let source = G::source(&gamestate).clone();
+        let mut results = Vec::new();
+        let forked = self.fork(&mut gamestate, source, input, 0, &mut results);
+
+        let mut outcomes = Vec::new();
+        for (source, result) in results {
+            if let Err(error) = &result {
+                let feedback = G::feedback(&mut gamestate);
+                feedback.failure(&source, Message::from(error));
+                if let Some(did_you_mean) = error.downcast_ref::<DidYouMean>() {
+                    feedback.failure(&source, did_you_mean.message());
+                }
+            }
+            outcomes.push(CommandOutcome::from_result(result));
+        }
+        if forked {
+            CommandOutcome::forked(outcomes)
+        } else {
+            outcomes.into_iter().sum()
+        }
+    }
+
+    fn fork(
+        &self,
+        gamestate: &mut G::GameState<'_>,
+        source: CommandSource,
+        input: &str,
+        depth: usize,
+        results: &mut Vec<(CommandSource, anyhow::Result<CommandResult>)>,
+    ) -> bool
+    where
+        G: SourceFamily,
+    {
+        if depth > MAX_REDIRECTS {
+            let message = Message::translatable(
+                "lieutenant.command.redirects",
+                vec![Message::text(MAX_REDIRECTS.to_string())],
+            );
+            results.push((source, Err(anyhow!(message))));
+            return false;
+        }
+
+        let candidates = self.redirect_automaton.candidates(input);
+        let mut redirect_err = None;
+        for id in candidates {
+            let redirect = &self.redirects[id.id];
+            match redirect.fork(G::with_source(gamestate, &source), input) {
+                Ok((sources, rest)) => {
+                    let mut forked = redirect.forks();
+                    for source in sources {
+                        forked |= self.fork(gamestate, source, &rest, depth + 1, results);
+                    }
+                    return forked;
+                }
+                Err(err) => redirect_err = Some(err),
+            }
+        }
+
+        // A redirect that failed to parse its arguments says more about what is wrong with the input than
+        // the commands, none of which start like the redirect.
+        let result = match (
+            self.call(G::with_source(gamestate, &source), input),
+            redirect_err,
+        ) {
+            (Err(_), Some(err)) => Err(err),
+            (result, _) => result,
+        };
+        results.push((source, result));
+        false
+    }
+
+    /// Returns ways to complete the input from every registered command and redirect. Ids are not
+    /// suggested, since they come from the registries of the game, see [`Dispatcher::suggest_with`].
+    pub fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        self.suggest_in(&(), input)
+    }
+
+    /// Like [`Dispatcher::suggest`], and suggests ids from the registries of the game state, see
+    /// [`GameStateFamily::registries`].
+    pub fn suggest_with(&self, gamestate: &G::GameState<'_>, input: &str) -> Vec<Suggestion> {
+        self.suggest_in(G::registries(gamestate), input)
+    }
+
+    fn suggest_in(&self, registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        self.suggest_redirected(registries, input, 0)
+    }
+
+    /// Suggests for the input, and for the input the redirects at its start lead to.
+    fn suggest_redirected(
+        &self,
+        registries: &dyn Registries,
+        input: &str,
+        depth: usize,
+    ) -> Vec<Suggestion> {
+        let mut suggestions = Vec::new();
+        let commands = self
+            .commands
+            .iter()
+            .map(|command| command.suggest_in(registries, input));
+        let redirects = self
+            .redirects
+            .iter()
+            .map(|redirect| redirect.suggest_in(registries, input));
+        for command in commands.chain(redirects) {
+            for suggestion in command {
+                if !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+
+        if depth < MAX_REDIRECTS {
+            for redirect in &self.redirects {
+                let rest = match redirect.rest(registries, input) {
+                    Some(rest) => rest,
+                    None => continue,
+                };
+                // Suggestions for the redirected input are moved back to where the rest starts in the input.
+                // Those that start in the target have to keep it, and only what follows is suggested.
+                let target = redirect.target();
+                let offset = input.len() - rest.len();
+                let redirected = format!("{}{}", target, rest);
+                for suggestion in self.suggest_redirected(registries, &redirected, depth + 1) {
+                    let kept = match redirected.get(suggestion.start..target.len()) {
+                        Some(kept) => kept,
+                        None if suggestion.start >= target.len() => "",
+                        None => continue,
+                    };
+                    let text = match suggestion.text.strip_prefix(kept) {
+                        Some(text) => String::from(text),
+                        None => continue,
+                    };
+                    let suggestion = Suggestion {
+                        start: offset + suggestion.start.max(target.len()) - target.len(),
+                        text,
+                    };
+                    if !suggestions.contains(&suggestion) {
+                        suggestions.push(suggestion);
+                    }
+                }
+            }
+        }
+        suggestions
+    }
+
+    /// Builds the brigadier graph of every registered command, which the client needs to complete commands.
+    /// Redirects point to the literals of their target, or to the root if the target has none.
+    pub fn command_tree(&self) -> CommandTree {
+        let mut tree = CommandTree::new();
+        for command in &self.commands {
+            for mut path in command.brigadier() {
+                strip_slash(&mut path);
+                tree.add_path(&path);
+            }
+        }
+
+        // Targets are looked up once every path is in, since a redirect can lead to its own literals.
+        let mut redirects = Vec::new();
+        for redirect in &self.redirects {
+            for mut path in redirect.brigadier() {
+                strip_slash(&mut path);
+                let node = path
+                    .into_iter()
+                    .fold(CommandTree::ROOT, |node, kind| tree.add_child(node, kind));
+                if node != CommandTree::ROOT {
+                    redirects.push((node, redirect.target()));
+                }
+            }
+        }
+        for (node, target) in redirects {
+            let target = self.separator.skip(target).trim_start_matches('/');
+            tree.nodes[node].redirect =
+                self.separator
+                    .words(target)
+                    .try_fold(CommandTree::ROOT, |node, word| {
+                        tree.nodes[node]
+                            .children
+                            .iter()
+                            .copied()
+                            .find(|&child| tree.nodes[child].kind == NodeKind::Literal(word.into()))
+                    });
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::argument::{EntitySelector, IntRange, Registries, Vec3};
+    use crate::brigadier::{ArgumentType, NodeKind};
+    use crate::command::{
+        builder::{literal, space, CommandBuilder},
+        CommandOutcome, GameStateFamily, RefMut,
+    };
+    use crate::feedback::{Message, Recorder};
+    use crate::parser::{LiteralMode, Opt, Separator, Suggestion};
+    use crate::source::{CommandSource, Sourced};
+
+    use super::{edit_distance, DidYouMean, Dispatcher};
+
+    struct Counters;
+
+    impl GameStateFamily for Counters {
+        type GameState<'a> = (&'a mut usize, &'a mut usize);
+
+        fn reborrow<'b>(gamestate: &'b mut Self::GameState<'_>) -> Self::GameState<'b> {
+            (&mut *gamestate.0, &mut *gamestate.1)
+        }
+    }
+
+    #[test]
+    fn fresh_borrows() {
+        let mut dispatcher = Dispatcher::<Counters, usize>::new();
+        dispatcher
+            .register(literal("/echo").space().arg::<u32>().on_call(|arg: u32| {
+                move |x: &mut usize, y: &mut usize| {
+                    *x += arg as usize;
+                    *y += 1;
+                    *x
+                }
+            }))
+            .unwrap();
+
+        let mut x = 0;
+        let mut y = 0;
+        for _tick in 0..3 {
+            assert!(dispatcher.call((&mut x, &mut y), "/echo 10").is_ok());
+        }
+        assert_eq!((x, y), (30, 3));
+        assert!(dispatcher.call((&mut x, &mut y), "/ech 10").is_err());
+    }
+
+    #[test]
+    fn register_after_call() {
+        let mut dispatcher = Dispatcher::<(), u32>::new();
+        dispatcher
+            .register(literal("/one").on_call(|| || 1))
+            .unwrap();
+        dispatcher.build();
+        assert_eq!(dispatcher.call((), "/one").unwrap(), 1);
+        assert!(dispatcher.call((), "/two").is_err());
+
+        dispatcher
+            .register(literal("/two").on_call(|| || 2))
+            .unwrap();
+        assert_eq!(dispatcher.call((), "/two").unwrap(), 2);
+        assert_eq!(dispatcher.call((), "/one").unwrap(), 1);
+    }
+
+    #[test]
+    fn overlapping() {
+        let mut dispatcher = Dispatcher::<RefMut<Vec<&'static str>>, ()>::new();
+        dispatcher
+            .register(
+                literal("/time")
+                    .space()
+                    .followed_by(literal("set"))
+                    .on_call(|| |log: &mut Vec<&'static str>| log.push("set")),
+            )
+            .unwrap();
+        dispatcher
+            .register(
+                literal("/time")
+                    .space()
+                    .followed_by(literal("add"))
+                    .on_call(|| |log: &mut Vec<&'static str>| log.push("add")),
+            )
+            .unwrap();
+
+        let mut log = Vec::new();
+        dispatcher.call((&mut log,), "/time add").unwrap();
+        dispatcher.call((&mut log,), "/time set").unwrap();
+        assert!(dispatcher.call((&mut log,), "/time sub").is_err());
+        assert_eq!(log, vec!["add", "set"]);
+    }
+
+    #[test]
+    fn suggest() {
+        let mut dispatcher = Dispatcher::<(), ()>::new();
+        dispatcher
+            .register(
+                literal("/time")
+                    .space()
+                    .followed_by(literal("set"))
+                    .on_call(|| || ()),
+            )
+            .unwrap();
+        dispatcher
+            .register(
+                literal("/time")
+                    .space()
+                    .followed_by(literal("add"))
+                    .on_call(|| || ()),
+            )
+            .unwrap();
+
+        let texts = |input| {
+            dispatcher
+                .suggest(input)
+                .into_iter()
+                .map(|s| (s.start, s.text))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts("/ti"), vec![(0, String::from("/time"))]);
+        assert_eq!(
+            texts("/time "),
+            vec![(6, String::from("set")), (6, String::from("add"))]
+        );
+        assert_eq!(texts("/time A"), vec![(6, String::from("add"))]);
+        assert!(texts("/time add").iter().all(|(start, _)| *start == 6));
+    }
+
+    #[test]
+    fn command_tree() {
+        let mut dispatcher = Dispatcher::<(), ()>::new();
+        dispatcher
+            .register(
+                literal("/time set")
+                    .space()
+                    .arg::<u32>()
+                    .on_call(|_: u32| || ()),
+            )
+            .unwrap();
+        dispatcher
+            .register(
+                literal("/time")
+                    .space()
+                    .followed_by(literal("add"))
+                    .followed_by(Opt::new(space().arg::<u32>()))
+                    .on_call(|_: Option<(u32,)>| || ()),
+            )
+            .unwrap();
+
+        let tree = dispatcher.command_tree();
+        let kinds = tree
+            .nodes
+            .iter()
+            .map(|node| (&node.kind, node.executable, node.children.clone()))
+            .collect::<Vec<_>>();
+        let time = NodeKind::Literal(String::from("time"));
+        let set = NodeKind::Literal(String::from("set"));
+        let add = NodeKind::Literal(String::from("add"));
+        let number = NodeKind::argument(
+            "number",
+            ArgumentType::Long {
+                min: Some(0),
+                max: Some(u32::MAX.into()),
+            },
+        );
+        assert_eq!(
+            kinds,
+            vec![
+                (&NodeKind::Root, false, vec![1]),
+                (&time, false, vec![2, 4]),
+                (&set, false, vec![3]),
+                (&number, true, vec![]),
+                (&add, true, vec![5]),
+                (&number, true, vec![]),
+            ]
+        );
+    }
+
+    struct World {
+        entities: Vec<(u128, [f64; 3])>,
+    }
+
+    impl Registries for World {}
+
+    fn execute_dispatcher() -> Dispatcher<Sourced<World>, [f64; 3]> {
+        let mut dispatcher = Dispatcher::<Sourced<World>, [f64; 3]>::new();
+        dispatcher
+            .register(literal("/tp").space().arg::<Vec3>().on_call(|pos: Vec3| {
+                move |_: &mut World, source: &CommandSource| pos.resolve(source)
+            }))
+            .unwrap();
+        dispatcher
+            .register_redirect(
+                literal("/execute as")
+                    .space()
+                    .arg::<EntitySelector>()
+                    .on_fork("/execute ", |targets: EntitySelector| {
+                        move |world: &mut World, source: &CommandSource| {
+                            world
+                                .entities
+                                .iter()
+                                .filter(|(id, _)| {
+                                    !targets.is_executor() || source.entity == Some(*id)
+                                })
+                                .map(|(id, _)| source.clone().with_entity(Some(*id)))
+                                .collect()
+                        }
+                    }),
+            )
+            .unwrap();
+        dispatcher
+            .register_redirect(
+                literal("/execute at")
+                    .space()
+                    .arg::<EntitySelector>()
+                    .on_fork("/execute ", |targets: EntitySelector| {
+                        move |world: &mut World, source: &CommandSource| {
+                            world
+                                .entities
+                                .iter()
+                                .filter(|(id, _)| {
+                                    !targets.is_executor() || source.entity == Some(*id)
+                                })
+                                .map(|(_, pos)| source.clone().with_position(*pos))
+                                .collect()
+                        }
+                    }),
+            )
+            .unwrap();
+        dispatcher
+            .register_redirect(literal("/execute run").on_redirect("/", || {
+                |_: &mut World, source: &CommandSource| vec![source.clone()]
+            }))
+            .unwrap();
+        dispatcher
+    }
+
+    #[test]
+    fn execute() {
+        let dispatcher = execute_dispatcher();
+        let mut world = World {
+            entities: vec![(1, [0.0, 64.0, 0.0]), (2, [10.5, 70.0, -3.5])],
+        };
+        let source = CommandSource::console().with_position([5.0, 5.0, 5.0]);
+
+        let results =
+            dispatcher.call_forked((&mut world, &source), "/execute as @e at @s run tp ~ ~1 ~");
+        let results = results
+            .into_iter()
+            .map(|(source, result)| (source.entity, result.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![(Some(1), [0.0, 65.0, 0.0]), (Some(2), [10.5, 71.0, -3.5])]
+        );
+
+        let results = dispatcher.call_forked((&mut world, &source), "/tp ~1 ~ ~");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, source);
+        assert_eq!(results[0].1.as_ref().unwrap(), &[6.0, 5.0, 5.0]);
+
+        let results = dispatcher.call_forked((&mut world, &source), "/execute as @e run tp up");
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(_, result)| result.is_err()));
+
+        world.entities.clear();
+        assert!(dispatcher
+            .call_forked((&mut world, &source), "/execute as @e run tp ~ ~ ~")
+            .is_empty());
+    }
+
+    #[test]
+    fn redirect_errors() {
+        let dispatcher = execute_dispatcher();
+        let mut world = World {
+            entities: vec![(1, [0.0; 3])],
+        };
+        let source = CommandSource::console();
+
+        // The redirect fails to parse the selector, which tells more than that no command starts with `/execute`.
+        let results = dispatcher.call_forked((&mut world, &source), "/execute as @x run tp ~ ~ ~");
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            Message::from(results[0].1.as_ref().unwrap_err()),
+            Message::translatable(
+                "argument.entity.selector.unknown",
+                vec![Message::text("@x")]
+            )
+        );
+
+        let mut dispatcher = Dispatcher::<Sourced<World>, [f64; 3]>::new();
+        dispatcher
+            .register_redirect(literal("/loop").on_redirect("/loop", || {
+                |_: &mut World, source: &CommandSource| vec![source.clone()]
+            }))
+            .unwrap();
+        let results = dispatcher.call_forked((&mut world, &source), "/loop");
+        assert_eq!(
+            Message::from(results[0].1.as_ref().unwrap_err()),
+            Message::translatable("lieutenant.command.redirects", vec![Message::text("64")])
+        );
+    }
+
+    #[test]
+    fn redirect_suggestions() {
+        let dispatcher = execute_dispatcher();
+        let texts = |input| {
+            dispatcher
+                .suggest(input)
+                .into_iter()
+                .map(|s| (s.start, s.text))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            texts("/execute as @s run t"),
+            vec![(19, String::from("tp"))]
+        );
+        assert_eq!(
+            texts("/execute at @s as @e r"),
+            vec![(21, String::from("run"))]
+        );
+        assert!(texts("/execute as @s run tp ~ ~ ~").is_empty());
+    }
+
+    #[test]
+    fn redirect_tree() {
+        let tree = execute_dispatcher().command_tree();
+        let find = |parent: usize, name: &str| {
+            tree.nodes[parent]
+                .children
+                .iter()
+                .copied()
+                .find(|&child| match &tree.nodes[child].kind {
+                    NodeKind::Literal(literal) => literal == name,
+                    NodeKind::Argument { name: arg, .. } => arg == name,
+                    NodeKind::Root => false,
+                })
+                .unwrap()
+        };
+        let execute = find(0, "execute");
+        let as_targets = find(find(execute, "as"), "targets");
+        assert_eq!(tree.nodes[as_targets].redirect, Some(execute));
+        assert!(!tree.nodes[as_targets].executable);
+        assert_eq!(tree.nodes[find(execute, "run")].redirect, Some(0));
+        assert_eq!(tree.paths().len(), 1);
+    }
+
+    #[test]
+    fn outcome() {
+        let mut dispatcher = Dispatcher::<Sourced<World>, i32>::new();
+        dispatcher
+            .register(
+                literal("/count")
+                    .on_call(|| |world: &mut World, _: &CommandSource| world.entities.len() as i32),
+            )
+            .unwrap();
+        dispatcher
+            .register_redirect(literal("/execute as @e").on_fork("/execute ", || {
+                |world: &mut World, source: &CommandSource| {
+                    world
+                        .entities
+                        .iter()
+                        .map(|(id, _)| source.clone().with_entity(Some(*id)))
+                        .collect()
+                }
+            }))
+            .unwrap();
+        dispatcher
+            .register_redirect(literal("/execute run").on_redirect("/", || {
+                |_: &mut World, source: &CommandSource| vec![source.clone()]
+            }))
+            .unwrap();
+
+        let mut world = World {
+            entities: vec![(1, [0.0; 3]), (2, [0.0; 3]), (3, [0.0; 3])],
+        };
+        let source = CommandSource::console();
+        let mut execute = |input| dispatcher.execute_forked((&mut world, &source), input);
+        assert_eq!(execute("/count"), CommandOutcome::new(1, 3));
+        assert_eq!(execute("/cuont"), CommandOutcome::FAILURE);
+        assert_eq!(execute("/execute run count"), CommandOutcome::new(1, 3));
+        assert_eq!(
+            execute("/execute as @e run count"),
+            CommandOutcome::new(3, 3)
+        );
+        assert_eq!(execute("/execute as @e run cuont"), CommandOutcome::FAILURE);
+        assert_eq!(
+            dispatcher.execute((&mut world, &source), "/count"),
+            CommandOutcome::success(3)
+        );
+    }
+
+    #[test]
+    fn did_you_mean() {
+        let mut dispatcher = Dispatcher::<Sourced<Recorder>, ()>::new();
+        for command in &["/time set", "/time add", "/teleport"] {
+            dispatcher
+                .register(
+                    literal(command)
+                        .space()
+                        .arg::<u32>()
+                        .on_call(|_: u32| |_: &mut Recorder, _: &CommandSource| ()),
+                )
+                .unwrap();
+        }
+
+        let mut recorder = Recorder::default();
+        let source = CommandSource::console();
+        let error = |input| {
+            dispatcher
+                .call((&mut Recorder::default(), &source), input)
+                .unwrap_err()
+        };
+        let suggestions = |input| error(input).downcast_ref::<DidYouMean>().cloned();
+        assert_eq!(
+            Message::from(&error("/TIEM set 5")),
+            Message::translatable("command.unknown.command", vec![])
+        );
+        let did_you_mean = suggestions("/TIEM set 5").unwrap();
+        assert_eq!(did_you_mean.position, 0);
+        assert_eq!(did_you_mean.suggestions, vec!["/time"]);
+        let did_you_mean = suggestions("/time  sdd 5").unwrap();
+        assert_eq!(
+            (did_you_mean.position, did_you_mean.word.as_str()),
+            (7, "sdd")
+        );
+        assert_eq!(did_you_mean.suggestions, vec!["add"]);
+        assert_eq!(suggestions("/xyzzy 5"), None);
+        assert_eq!(edit_distance(&['a', 'b'], &['b', 'a', 'c']), 2);
+        assert_eq!(edit_distance(&['a', 'b', 'c'], &['c', 'a']), 3);
+
+        dispatcher.run((&mut recorder, &source), "/teleprot 5");
+        assert_eq!(
+            recorder.messages[1].2.to_string(),
+            "Did you mean /teleport?"
+        );
+    }
+
+    #[test]
+    fn argument_errors() {
+        let mut dispatcher = Dispatcher::<Sourced<Recorder>, ()>::new();
+        dispatcher
+            .register(
+                literal("/time set")
+                    .space()
+                    .arg::<u32>()
+                    .on_call(|_: u32| |_: &mut Recorder, _: &CommandSource| ()),
+            )
+            .unwrap();
+
+        let source = CommandSource::console();
+        let error = dispatcher
+            .call((&mut Recorder::default(), &source), "/time set -5")
+            .unwrap_err();
+        assert_eq!(
+            Message::from(&error),
+            Message::translatable(
+                "argument.integer.low",
+                vec![Message::text("0"), Message::text("-5")]
+            )
+        );
+    }
+
+    #[test]
+    fn furthest_candidate() {
+        let mut dispatcher = Dispatcher::<(), ()>::new();
+        dispatcher
+            .register(
+                literal("/pair")
+                    .space()
+                    .arg::<IntRange>()
+                    .space()
+                    .arg::<u32>()
+                    .on_call(|_: IntRange, _: u32| || ()),
+            )
+            .unwrap();
+        dispatcher
+            .register(
+                literal("/pair")
+                    .space()
+                    .arg::<u32>()
+                    .space()
+                    .arg::<IntRange>()
+                    .on_call(|_: u32, _: IntRange| || ()),
+            )
+            .unwrap();
+
+        // Both commands match the input, but the first one gets further before it fails.
+        let error = dispatcher.call((), "/pair -1 -2").unwrap_err();
+        assert_eq!(
+            Message::from(&error),
+            Message::translatable(
+                "argument.integer.low",
+                vec![Message::text("0"), Message::text("-2")]
+            )
+        );
+    }
+
+    #[test]
+    fn literal_modes() {
+        let mut dispatcher = Dispatcher::<Counters, usize>::new();
+        let commands = [
+            ("/gamemode", LiteralMode::Prefix),
+            ("/gamerule", LiteralMode::Prefix),
+            ("/give", LiteralMode::CaseSensitive),
+        ];
+        for (id, (command, mode)) in commands.iter().enumerate() {
+            dispatcher
+                .register(
+                    literal(command)
+                        .with_mode(*mode)
+                        .on_call(move || move |_: &mut usize, _: &mut usize| id),
+                )
+                .unwrap();
+        }
+
+        let (mut x, mut y) = (0, 0);
+        let mut call = |input| dispatcher.call((&mut x, &mut y), input);
+        assert_eq!(call("/GAMEM").unwrap(), 0);
+        assert_eq!(call("/gamer").unwrap(), 1);
+        assert_eq!(call("/give").unwrap(), 2);
+        assert!(call("/GIVE").is_err());
+        assert!(call("/gi").is_err());
+        assert_eq!(
+            Message::from(&call("/gam").unwrap_err()),
+            Message::translatable(
+                "lieutenant.command.ambiguous",
+                vec![Message::text("/gam"), Message::text("/gamemode, /gamerule")]
+            )
+        );
+    }
+
+    #[test]
+    fn separators() {
+        let register = |dispatcher: &mut Dispatcher<RefMut<Vec<String>>, ()>| {
+            dispatcher
+                .register(
+                    literal("/time set")
+                        .space()
+                        .arg::<u32>()
+                        .on_call(|time: u32| {
+                            move |log: &mut Vec<String>| log.push(time.to_string())
+                        }),
+                )
+                .unwrap();
+            dispatcher
+                .register(
+                    literal("/tp")
+                        .space()
+                        .arg::<Vec3>()
+                        .on_call(|_: Vec3| |log: &mut Vec<String>| log.push(String::from("tp"))),
+                )
+                .unwrap();
+            dispatcher
+                .register(
+                    literal("/give")
+                        .space()
+                        .arg::<EntitySelector>()
+                        .space()
+                        .arg::<u32>()
+                        .on_call(|_: EntitySelector, count: u32| {
+                            move |log: &mut Vec<String>| log.push(format!("give {}", count))
+                        }),
+                )
+                .unwrap();
+        };
+
+        let mut whitespace = Dispatcher::new();
+        register(&mut whitespace);
+        let mut strict = Dispatcher::with_separator(Separator::Space).unwrap();
+        register(&mut strict);
+        let mut commas = Dispatcher::with_separator(Separator::chars([',']).unwrap()).unwrap();
+        register(&mut commas);
+        assert!(Dispatcher::<RefMut<Vec<String>>, ()>::with_separator(
+            Separator::Chars(Vec::new())
+        )
+        .is_err());
+
+        let mut log = Vec::new();
+        for input in &["/time  set\t5", "/tp 1   2 3 "] {
+            assert!(whitespace.call((&mut log,), input).is_ok(), "{}", input);
+            assert!(strict.call((&mut log,), input).is_err(), "{}", input);
+        }
+        assert!(strict.call((&mut log,), "/time set 6").is_ok());
+        assert!(strict.call((&mut log,), "/time set 6 ").is_err());
+        assert!(strict.call((&mut log,), "/tp 1 2 3").is_ok());
+        assert!(commas.call((&mut log,), "/time,set,,7").is_ok());
+        assert!(commas.call((&mut log,), "/tp,1,2,3").is_ok());
+        assert!(commas.call((&mut log,), "/time set 8").is_err());
+        assert_eq!(log, vec!["5", "tp", "6", "tp", "7", "tp"]);
+        assert!(commas.call((&mut log,), "/give,Notch,9").is_ok());
+        assert!(commas.call((&mut log,), "/give,@a,10").is_ok());
+        assert!(commas.call((&mut log,), "/give,Notch 9").is_err());
+        assert_eq!(log[6..], ["give 9", "give 10"]);
+
+        assert_eq!(
+            commas.suggest("/time"),
+            vec![Suggestion {
+                start: 0,
+                text: String::from("/time,set"),
+            }]
+        );
+        assert_eq!(
+            commas.did_you_mean("/time,sdt,1").unwrap().suggestions,
+            vec!["set"]
+        );
+    }
+}
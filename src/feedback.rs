@@ -0,0 +1,216 @@
+//! Messages commands send back, like "Teleported Notch to 1, 2, 3", and where they end up.
+
+use std::fmt;
+
+use indexmap::IndexMap;
+
+use crate::argument::{Json, Registries};
+use crate::parser::Located;
+use crate::source::{CommandSource, SourceFamily, Sourced};
+use crate::translation::English;
+
+/// A chat message the client can translate, or plain text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Text(String),
+    /// A key of the language files, like `commands.teleport.success.location.single`, with the
+    /// values for its placeholders.
+    Translatable {
+        key: String,
+        args: Vec<Message>,
+    },
+}
+
+impl Message {
+    pub fn text(text: impl Into<String>) -> Self {
+        Message::Text(text.into())
+    }
+
+    pub fn translatable(key: impl Into<String>, args: Vec<Message>) -> Self {
+        Message::Translatable {
+            key: key.into(),
+            args,
+        }
+    }
+
+    /// The message as a text component.
+    pub fn to_json(&self) -> Json {
+        let mut component = IndexMap::new();
+        match self {
+            Message::Text(text) => {
+                component.insert(String::from("text"), Json::String(text.clone()));
+            }
+            Message::Translatable { key, args } => {
+                component.insert(String::from("translate"), Json::String(key.clone()));
+                if !args.is_empty() {
+                    let args = args.iter().map(Message::to_json).collect();
+                    component.insert(String::from("with"), Json::Array(args));
+                }
+            }
+        }
+        Json::Object(component)
+    }
+}
+
+/// Shows the message in English, see [`Message::translate`].
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.translate(&English))
+    }
+}
+
+impl std::error::Error for Message {}
+
+/// Errors that carry a [`Message`] keep it, any other error becomes its text.
+impl From<&anyhow::Error> for Message {
+    fn from(error: &anyhow::Error) -> Self {
+        if let Some(located) = error.downcast_ref::<Located>() {
+            return Message::from(&located.error);
+        }
+        match error.downcast_ref::<Message>() {
+            Some(message) => message.clone(),
+            None => Message::Text(error.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackKind {
+    /// Only for whoever ran the command.
+    Success,
+    /// For whoever ran the command, and also for operators and the log.
+    Broadcast,
+    Failure,
+}
+
+/// Where the messages of commands go. The game decides who gets to see them, for example by dropping
+/// successes while the `sendCommandFeedback` gamerule is off.
+pub trait Feedback {
+    /// Sends a message to the executor of `source`, which stays the same through `/execute as`.
+    fn send(&mut self, source: &CommandSource, kind: FeedbackKind, message: Message);
+
+    fn success(&mut self, source: &CommandSource, message: Message) {
+        self.send(source, FeedbackKind::Success, message)
+    }
+
+    fn broadcast(&mut self, source: &CommandSource, message: Message) {
+        self.send(source, FeedbackKind::Broadcast, message)
+    }
+
+    fn failure(&mut self, source: &CommandSource, message: Message) {
+        self.send(source, FeedbackKind::Failure, message)
+    }
+}
+
+/// Drops every message.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Silent;
+
+impl Feedback for Silent {
+    fn send(&mut self, _source: &CommandSource, _kind: FeedbackKind, _message: Message) {}
+}
+
+/// No registries, so a silent game can be used with [`Sourced`] on its own.
+impl Registries for Silent {}
+
+/// Keeps every message with the name of the executor it was sent to, for tests and harnesses.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recorder {
+    pub messages: Vec<(String, FeedbackKind, Message)>,
+}
+
+impl Feedback for Recorder {
+    fn send(&mut self, source: &CommandSource, kind: FeedbackKind, message: Message) {
+        self.messages.push((source.name(), kind, message));
+    }
+}
+
+/// No registries, so a recorder can be used with [`Sourced`] on its own.
+impl Registries for Recorder {}
+
+/// A [`SourceFamily`] whose game states also carry the [`Feedback`] of the game.
+pub trait FeedbackFamily: SourceFamily {
+    fn feedback<'b>(gamestate: &'b mut Self::GameState<'_>) -> &'b mut dyn Feedback;
+}
+
+impl<T: Feedback + Registries + 'static> FeedbackFamily for Sourced<T> {
+    fn feedback<'b>(gamestate: &'b mut Self::GameState<'_>) -> &'b mut dyn Feedback {
+        &mut *gamestate.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use crate::command::builder::{literal, CommandBuilder};
+    use crate::command::CommandOutcome;
+    use crate::dispatcher::Dispatcher;
+    use crate::source::{CommandSource, Sourced};
+
+    use super::{Feedback, FeedbackKind, Message, Recorder};
+
+    #[test]
+    fn message() {
+        let message = Message::translatable(
+            "commands.time.set",
+            vec![Message::text("1000"), Message::translatable("a", vec![])],
+        );
+        assert_eq!(
+            message.to_json().to_string(),
+            "{\"translate\":\"commands.time.set\",\"with\":[{\"text\":\"1000\"},{\"translate\":\"a\"}]}"
+        );
+        assert_eq!(message.to_string(), "commands.time.set");
+
+        let error = anyhow::Error::new(message.clone());
+        assert_eq!(Message::from(&error), message);
+        assert_eq!(
+            Message::from(&anyhow!("Not a number")),
+            Message::text("Not a number")
+        );
+    }
+
+    #[test]
+    fn dispatch() {
+        let mut dispatcher = Dispatcher::<Sourced<Recorder>, ()>::new();
+        dispatcher
+            .register(
+                literal("/time set")
+                    .space()
+                    .arg::<u32>()
+                    .on_call(|time: u32| {
+                        move |feedback: &mut Recorder, source: &CommandSource| {
+                            let args = vec![Message::text(time.to_string())];
+                            feedback.broadcast(
+                                source,
+                                Message::translatable("commands.time.set", args),
+                            );
+                        }
+                    }),
+            )
+            .unwrap();
+
+        let mut recorder = Recorder::default();
+        let source = CommandSource::player("Notch", 1);
+        assert_eq!(
+            dispatcher.run((&mut recorder, &source), "/time set 100"),
+            CommandOutcome::success(1)
+        );
+        assert_eq!(
+            dispatcher.run((&mut recorder, &source), "/time sat 100"),
+            CommandOutcome::FAILURE
+        );
+
+        assert_eq!(recorder.messages.len(), 3);
+        assert_eq!(
+            recorder.messages[0],
+            (
+                String::from("Notch"),
+                FeedbackKind::Broadcast,
+                Message::translatable("commands.time.set", vec![Message::text("100")])
+            )
+        );
+        assert_eq!(recorder.messages[1].1, FeedbackKind::Failure);
+        assert_eq!(recorder.messages[2].2.to_string(), "Did you mean set?");
+    }
+}
@@ -1,8 +1,19 @@
+// The test of the most arguments a command can have nests deeper than the default limit.
+#![cfg_attr(test, recursion_limit = "512")]
+
 pub mod argument;
+pub mod brigadier;
 pub mod command;
+pub mod dispatcher;
+pub mod feedback;
 mod generic;
 pub mod parser;
 pub mod regex;
+pub mod source;
+pub mod translation;
+
+#[cfg(feature = "derive")]
+pub use lieutenant_derive::{Argument, Command};
 
 #[cfg(test)]
 extern crate quickcheck;
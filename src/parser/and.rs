@@ -1,16 +1,35 @@
-use crate::generic::{Combine, CombinedTuples, Tuple};
+use anyhow::anyhow;
 
-use super::IterParser;
+use crate::{
+    argument::Registries,
+    brigadier::NodeKind,
+    feedback::Message,
+    generic::{Combine, CombinedTuples, Tuple},
+};
+
+use super::{IterParser, Located, Separator, Suggestion};
 
 pub struct And<A, B> {
     pub(crate) a: A,
     pub(crate) b: B,
 }
 
-pub struct AndState<A: IterParser, B: IterParser> {
-    pub(crate) a_state: Option<A::ParserState>,
-    pub(crate) a_ext: Option<(A::Extract, usize)>,
-    pub(crate) b_state: Option<B::ParserState>,
+pub enum AndState<A: IterParser, B: IterParser> {
+    /// We are looking for the next match of A, starting from this state. `None` stands for the default state,
+    /// so the first attempt at A never has to clone a state to remember where it started.
+    A(Option<A::ParserState>),
+    /// A has matched, and we are trying B on what A left of the input.
+    B {
+        /// The state A produced the match from, `None` for the default state. Parsing A from it again gives
+        /// the same match. It is boxed, because the states of nested Ands would otherwise grow with the
+        /// square of the depth and overflow the stack on long chains of arguments.
+        from: Option<Box<A::ParserState>>,
+        /// Number of bytes of the input consumed by A.
+        consumed: usize,
+        /// The extract of A, until it is handed out together with the first match of B.
+        ext: Option<A::Extract>,
+        b_state: B::ParserState,
+    },
 }
 
 impl<A, B> Default for AndState<A, B>
@@ -19,10 +38,30 @@ where
     B: IterParser,
 {
     fn default() -> Self {
-        Self {
-            a_state: Some(A::ParserState::default()),
-            b_state: None,
-            a_ext: None,
+        Self::A(None)
+    }
+}
+
+impl<A, B> Clone for AndState<A, B>
+where
+    A: IterParser,
+    B: IterParser,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::A(a_state) => Self::A(a_state.clone()),
+            // The extract is only a cache of what replaying `from` gives, so it is left out of the clone.
+            Self::B {
+                from,
+                consumed,
+                ext: _,
+                b_state,
+            } => Self::B {
+                from: from.clone(),
+                consumed: *consumed,
+                ext: None,
+                b_state: b_state.clone(),
+            },
         }
     }
 }
@@ -30,33 +69,25 @@ where
 impl<A, B> std::fmt::Debug for AndState<A, B>
 where
     A: IterParser,
-    A::Extract: std::fmt::Debug,
     A::ParserState: std::fmt::Debug,
     B: IterParser,
-    B::Extract: std::fmt::Debug,
     B::ParserState: std::fmt::Debug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // match (self.a_state, self.b_state, self.a_ext) {
-        //     (None, None, None) => {}
-        //     (None, None, Some(_)) => {}
-        //     (None, Some(_), None) => {}
-        //     (None, Some(_), Some(_)) => {}
-        //     (Some(_), None, None) => {}
-        //     (Some(_), None, Some(_)) => {}
-        //     (Some(_), Some(_), None) => {}
-        //     (Some(_), Some(_), Some(_)) => {}
-        // }
-        match &self.a_ext {
-            Some((_ext, pos)) => write!(
+        match self {
+            Self::A(a_state) => write!(f, "(AndState::A: a_state: {:?} )", a_state),
+            Self::B {
+                from,
+                consumed,
+                ext,
+                b_state,
+            } => write!(
                 f,
-                "(AndState: a_state: {:?}, b_state: {:?}, a_ext: Some((ext:???, pos:{})) )",
-                self.a_state, self.b_state, pos
-            ),
-            None => write!(
-                f,
-                "(AndState: a_state: {:?}, b_state: {:?}, a_ext: None )",
-                self.a_state, self.b_state
+                "(AndState::B: from: {:?}, consumed: {}, ext: {}, b_state: {:?} )",
+                from,
+                consumed,
+                if ext.is_some() { "Some(???)" } else { "None" },
+                b_state
             ),
         }
     }
@@ -65,7 +96,6 @@ where
 impl<A, B> IterParser for And<A, B>
 where
     A: IterParser,
-    A::Extract: Clone, // I would love some help trying to remove this clone.
     B: IterParser,
     <<A as IterParser>::Extract as Tuple>::HList:
         Combine<<<B as IterParser>::Extract as Tuple>::HList>,
@@ -73,7 +103,10 @@ where
     type Extract = CombinedTuples<A::Extract, B::Extract>;
     type ParserState = AndState<A, B>;
 
-    #[allow(clippy::type_complexity, clippy::needless_return)]
+    // Instead of cloning the extract of A for every match of B, we remember the state A produced it from.
+    // The first match of B takes the extract, and later matches of B get it by parsing A again from that state.
+    // In the common case where B only has a single match, A is never parsed twice.
+    #[allow(clippy::type_complexity)]
     fn parse<'p>(
         &self,
         state: AndState<A, B>,
@@ -82,275 +115,143 @@ where
         anyhow::Result<(Self::Extract, &'p str)>,
         Option<AndState<A, B>>,
     ) {
-        let a_state = state.a_state;
-        let b_state = state.b_state;
-        let a_ext = state.a_ext;
-
-        match (a_state, b_state, a_ext) {
-            (None, None, None) => unreachable!("None, None, None"),
-            (None, None, Some(_)) => unreachable!("None, None, Some"),
-            (None, Some(_), None) => unreachable!("None, Some, None"),
-            (None, Some(b_state), Some((a_ext, a_out_index))) => {
-                // This state is reachable from the intial state (Some, None, None), and (Some, Some, None)
-
-                let (_, a_out) = input.split_at(a_out_index);
-
-                let (b_ext, b_state) = self.b.parse(b_state, a_out);
-
-                match b_ext {
-                    Ok((b_ext, b_out)) => {
-                        // We had a complete match we can return.
-
-                        match b_state {
-                            Some(b_state) => {
-                                // The B parser has more to give. This leads us back to this match arm on the next call.
-
-                                let a_out_index = a_out.len();
-
-                                let cloned_a_ext = a_ext.clone();
-                                (
-                                    Ok((a_ext.combine(b_ext), b_out)),
-                                    Some(AndState {
-                                        a_state: None,
-                                        a_ext: Some((cloned_a_ext, a_out_index)),
-                                        b_state: Some(b_state),
-                                    }),
-                                )
-                            }
-                            None => {
-                                // The B parser has no more to give, and in this match arm the A parser also has nothing more
-                                // to give, resulting in this beeing final match for the And.
-                                return (Ok((a_ext.combine(b_ext), b_out)), None);
-                            }
-                        }
+        self.parse_in(&(), state, input)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn parse_in<'p>(
+        &self,
+        registries: &dyn Registries,
+        state: AndState<A, B>,
+        input: &'p str,
+    ) -> (
+        anyhow::Result<(Self::Extract, &'p str)>,
+        Option<AndState<A, B>>,
+    ) {
+        // When A was parsed in this call, this is the state it continues from.
+        let mut a_next = None;
+
+        let (from, consumed, mut ext, b_state) = match state {
+            AndState::A(a_state) => {
+                let (a_res, next) =
+                    self.a
+                        .parse_in(registries, a_state.clone().unwrap_or_default(), input);
+                match a_res {
+                    Ok((ext, a_out)) => {
+                        a_next = Some(next);
+                        (
+                            a_state,
+                            input.len() - a_out.len(),
+                            Some(ext),
+                            B::ParserState::default(),
+                        )
                     }
                     Err(err) => {
-                        // We did not have a match
-
-                        match b_state {
-                            Some(b_state) => {
-                                // More possible matches from the B parser. This leads us back to this match branch on the next call to parse.
-                                let a_out_index = a_out.len();
-                                return (
-                                    Err(err),
-                                    Some(AndState {
-                                        a_state: None,
-                                        a_ext: Some((a_ext, a_out_index)),
-                                        b_state: Some(b_state),
-                                    }),
-                                );
-                            }
-                            None => {
-                                // No more matches comming from B parser, and since we in this branch dont expect any more from A, we know there wont
-                                // be any more matches.
-                                return (Err(err), None);
-                            }
-                        }
+                        // No match this time. If A has more to give, we try again on the next call.
+                        return (Err(err), next.map(|next| AndState::A(Some(next))));
                     }
                 }
             }
-            (Some(a_state), None, None) => {
-                // This is the entry point when calling parse with a fresh instance of Self::State::default(),
-                // This is also reachable from (Some, None, Some)
+            AndState::B {
+                from,
+                consumed,
+                ext,
+                b_state,
+            } => (from.map(|from| *from), consumed, ext, b_state),
+        };
 
-                let (a_ext, a_state) = self.a.parse(a_state, input);
+        let (b_res, b_next) = self.b.parse_in(registries, b_state, &input[consumed..]);
+
+        let result = match b_res {
+            Ok((b_ext, b_out)) => {
+                let a_ext = match ext.take() {
+                    Some(a_ext) => Ok(a_ext),
+                    None => match self
+                        .a
+                        .parse_in(registries, from.clone().unwrap_or_default(), input)
+                        .0
+                    {
+                        Ok((a_ext, a_out)) if input.len() - a_out.len() == consumed => Ok(a_ext),
+                        // A broke the determinism contract of `IterParser::parse`.
+                        _ => Err(anyhow!(Message::translatable(
+                            "lieutenant.parser.nondeterministic",
+                            vec![],
+                        ))),
+                    },
+                };
+                a_ext.map(|a_ext| (a_ext.combine(b_ext), b_out))
+            }
+            Err(err) => Err(Located::shift(err, consumed)),
+        };
 
-                match a_ext {
-                    Err(err) => {
-                        match a_state {
-                            None => {
-                                // A has no more possibilities, and we did not get a match, so we return the error, and signal that
-                                // the and has no possible matches.
-                                return (Err(err), None);
-                            }
-                            Some(a_state) => {
-                                // We did not have a match but we have more attempts for the A parser available, this leads us back to this
-                                // match arm on the next call.
-                                return (
-                                    Err(err),
-                                    Some(AndState {
-                                        a_state: Some(a_state),
-                                        a_ext: None,
-                                        b_state: None,
-                                    }),
-                                );
-                            }
-                        }
-                    }
-                    Ok((a_ext, a_out)) => {
-                        // We have had our first match here for the a parser, now we need to start looking for b matches.
-                        match a_state {
-                            Some(a_state) => {
-                                // This leads us into  (Some, Some, Some)
-                                let a_out_index = a_out.len();
-                                return self.parse(
-                                    AndState {
-                                        a_state: Some(a_state),
-                                        a_ext: Some((a_ext, a_out_index)),
-                                        b_state: Some(B::ParserState::default()),
-                                    },
-                                    input,
-                                );
-                            }
-                            None => {
-                                // No more inputs are possible for A, leading us into (None, Some,Some)
-
-                                let a_out_index = input.len() - a_out.len();
-                                return self.parse(
-                                    AndState {
-                                        a_state: None,
-                                        a_ext: Some((a_ext, a_out_index)),
-                                        b_state: Some(B::ParserState::default()),
-                                    },
-                                    input,
-                                );
-                            }
-                        }
-                    }
+        let next = match b_next {
+            // B has more to give for the current match of A.
+            Some(b_state) => Some(AndState::B {
+                from: from.map(Box::new),
+                consumed,
+                ext,
+                b_state,
+            }),
+            // B is done with this match of A, so we continue with the next match of A.
+            None => match a_next {
+                Some(a_next) => a_next,
+                None => {
+                    self.a
+                        .parse_in(registries, from.unwrap_or_default(), input)
+                        .1
                 }
             }
-            (Some(a_state), None, Some(_a_ext)) => {
-                // This branch is reachable from (Some, Some, Some)
-
-                // We had a match and no more matches are comming for B.
-                // This means that we should parse a again,thereby reset/update the a_ext,
-                // and finally reset b_state to default.
+            .map(|a_next| AndState::A(Some(a_next))),
+        };
 
-                // Note: this is the branch you are workin on.
-                //let a_state_clone = a_state.clone();
+        (result, next)
+    }
 
-                return self.parse(
-                    AndState {
-                        a_state: Some(a_state),
-                        a_ext: None,
-                        b_state: None,
-                    },
-                    input,
-                );
+    fn regex(&self) -> String {
+        format!("({})({})", &self.a.regex(), &self.b.regex())
+    }
 
-                //unreachable!("Some, None, Some")
-            }
-            (Some(a_state), Some(b_state), None) => {
-                // We can get in this branch from the (Some, Some, Some). This state happens when for a given input have
-                // had a match for A, and then gone through all possible states for B. We now need to update our attempt
-                // at parsing with the first parser A.
-
-                let (a_ext, a_state) = self.a.parse(a_state, input);
-
-                match (a_ext, a_state) {
-                    (Ok((a_ext, a_out)), None) => {
-                        // We got a new match and no more new ones are comming for a parser.
-                        // this leads us to (None,Some,Some)
-                        let a_out_index = a_out.len();
-                        return self.parse(
-                            AndState {
-                                a_state: None,
-                                a_ext: Some((a_ext, a_out_index)),
-                                b_state: Some(b_state),
-                            },
-                            input,
-                        );
-                    }
-                    (Ok((a_ext, a_out)), Some(a_state)) => {
-                        // This leads us into the Some, Some, Some branch
-                        let a_out_index = a_out.len();
-                        return self.parse(
-                            AndState {
-                                a_state: Some(a_state),
-                                a_ext: Some((a_ext, a_out_index)),
-                                b_state: Some(b_state),
-                            },
-                            input,
-                        );
-                    }
-                    (Err(err), None) => {
-                        // We had no match, and no more matches possible for the a parser.
-                        return (Err(err), None);
-                    }
-                    (Err(err), Some(a_state)) => {
-                        // We had no match but more matches are possible for the a parser.
-                        // This leads us back to this branch in the next call to parse.
-                        return (
-                            Err(err),
-                            Some(AndState {
-                                a_state: Some(a_state),
-                                a_ext: None,
-                                b_state: Some(b_state),
-                            }),
-                        );
-                    }
-                }
-            }
-            (Some(a_state), Some(b_state), Some((a_ext, a_out_index))) => {
-                // This state is reachable from the entrypoint (Some,None,None)
-                // and also (Some,Some,None)
-
-                // We continue trying different cases of the b_parser.
-                let (_, a_out) = input.split_at(a_out_index);
-                let (b_ext, b_state) = self.b.parse(b_state, a_out);
-
-                match (b_ext, b_state) {
-                    (Ok((b_ext, b_out)), None) => {
-                        // We had a match and no more matches are comming for B.
-                        // This means that on the next call we should parse a again,thereby
-                        // reset/update the a_ext, and finally reset b_state to default.
-
-                        // This results in the (Some, None, Some) on the next call.
-                        let cloned_a_ext = a_ext.clone();
-                        return (
-                            Ok((a_ext.combine(b_ext), b_out)),
-                            Some(AndState {
-                                a_state: Some(a_state),
-                                a_ext: Some((cloned_a_ext, a_out_index)),
-                                b_state: None,
-                            }),
-                        );
-                    }
-                    (Ok((b_ext, b_out)), Some(b_state)) => {
-                        // We had a match and there are more potential matches for B.
-                        // This results in (Some, Some, Some), returning to this branch on the next call.
-                        let cloned_a_ext = a_ext.clone();
-                        return (
-                            Ok((a_ext.combine(b_ext), b_out)),
-                            Some(AndState {
-                                a_state: Some(a_state),
-                                a_ext: Some((cloned_a_ext, a_out_index)),
-                                b_state: Some(b_state),
-                            }),
-                        );
-                    }
-                    (Err(err), None) => {
-                        // We found nothing and there are no more to get for the B parser.
-                        // We therefor reset the B state, and remove (a_ext, a_out).
-                        // This moves us into the (Some, Some, None) branch
-                        return (
-                            Err(err),
-                            Some(AndState {
-                                a_state: Some(a_state),
-                                a_ext: None,
-                                b_state: Some(B::ParserState::default()),
-                            }),
-                        );
-                    }
-                    (Err(err), Some(b_state)) => {
-                        // We found nothing, but there are more to get from the B parser
-                        // This sends us back to this branch on the next call to parse.
-                        return (
-                            Err(err),
-                            Some(AndState {
-                                a_state: Some(a_state),
-                                a_ext: Some((a_ext, a_out_index)),
-                                b_state: Some(b_state),
-                            }),
-                        );
-                    }
-                }
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        let b_paths = self.b.brigadier();
+        let mut paths = Vec::new();
+        for a_path in self.a.brigadier() {
+            for b_path in &b_paths {
+                paths.push(a_path.iter().chain(b_path).cloned().collect());
             }
         }
+        paths
     }
 
-    fn regex(&self) -> String {
-        format!("({})({})", &self.a.regex(), &self.b.regex())
+    fn set_separator(&mut self, separator: &Separator) {
+        self.a.set_separator(separator);
+        self.b.set_separator(separator);
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        self.suggest_in(&(), input)
+    }
+
+    fn suggest_in(&self, registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        let mut suggestions = self.a.suggest_in(registries, input);
+
+        // Every way A can match the input leaves a rest that B might complete.
+        let mut state = Some(A::ParserState::default());
+        while let Some(a_state) = state {
+            let (a_res, next) = self.a.parse_in(registries, a_state, input);
+            if let Ok((_, a_out)) = a_res {
+                let consumed = input.len() - a_out.len();
+                suggestions.extend(
+                    self.b
+                        .suggest_in(registries, a_out)
+                        .into_iter()
+                        .map(|suggestion| suggestion.shifted(consumed)),
+                );
+            }
+            state = next;
+        }
+
+        suggestions.dedup();
+        suggestions
     }
 }
 
@@ -358,13 +259,9 @@ mod tests {
 
     #[test]
     fn simple() {
-        let lit1 = crate::parser::Literal {
-            value: String::from("tp"),
-        };
+        let lit1 = crate::parser::Literal::new(String::from("tp"));
 
-        let lit2 = crate::parser::Literal {
-            value: String::from("me"),
-        };
+        let lit2 = crate::parser::Literal::new(String::from("me"));
 
         let and = crate::parser::And { a: lit1, b: lit2 };
 
@@ -375,7 +272,7 @@ mod tests {
         let res = eval.evaluate_all(input);
 
         assert!(res.len() == 1);
-        assert!(res.get(0).unwrap().is_ok());
+        assert!(res.first().unwrap().is_ok());
         assert!(res.len() == 1);
     }
 
@@ -383,13 +280,9 @@ mod tests {
     fn simple_opt_1() {
         let and = crate::parser::And {
             a: crate::parser::Opt {
-                parser: crate::parser::Literal {
-                    value: String::from("tp"),
-                },
-            },
-            b: crate::parser::Literal {
-                value: String::from("me"),
+                parser: crate::parser::Literal::new(String::from("tp")),
             },
+            b: crate::parser::Literal::new(String::from("me")),
         };
 
         let input = &mut "tp me";
@@ -403,15 +296,11 @@ mod tests {
 
     #[test]
     fn simple_opt_2() {
-        let lit1 = crate::parser::Literal {
-            value: String::from("tp"),
-        };
+        let lit1 = crate::parser::Literal::new(String::from("tp"));
 
         let opt1 = crate::parser::Opt { parser: lit1 };
 
-        let lit2 = crate::parser::Literal {
-            value: String::from("me"),
-        };
+        let lit2 = crate::parser::Literal::new(String::from("me"));
 
         let and = crate::parser::And { a: opt1, b: lit2 };
 
@@ -428,15 +317,11 @@ mod tests {
 
     #[test]
     fn simple_opt_3() {
-        let lit1 = crate::parser::Literal {
-            value: String::from("tp"),
-        };
+        let lit1 = crate::parser::Literal::new(String::from("tp"));
 
         let opt1 = crate::parser::Opt { parser: lit1 };
 
-        let lit2 = crate::parser::Literal {
-            value: String::from("me"),
-        };
+        let lit2 = crate::parser::Literal::new(String::from("me"));
 
         let opt2 = crate::parser::Opt { parser: lit2 };
 
@@ -457,15 +342,11 @@ mod tests {
     #[test]
     fn simple_opt_4() {
         for word in &["tp", "tango", "121", "œeœ", "ࢰࢰ", "😈😈😈"] {
-            let lit1 = crate::parser::Literal {
-                value: String::from(*word),
-            };
+            let lit1 = crate::parser::Literal::new(String::from(*word));
 
             let opt1 = crate::parser::Opt { parser: lit1 };
 
-            let lit2 = crate::parser::Literal {
-                value: String::from(*word),
-            };
+            let lit2 = crate::parser::Literal::new(String::from(*word));
 
             let opt2 = crate::parser::Opt { parser: lit2 };
 
@@ -482,4 +363,75 @@ mod tests {
             assert!(res.iter().all(|x| x.is_ok()));
         }
     }
+
+    #[test]
+    fn no_clone_extract() {
+        struct Handle(u32);
+
+        let and = crate::parser::And {
+            a: crate::parser::Map {
+                parser: crate::argument::U32Parser {},
+                map: Handle,
+            },
+            b: crate::parser::Opt {
+                parser: crate::parser::Literal::new(String::from("x")),
+            },
+        };
+
+        let eval = crate::parser::Evaluator::new(&and);
+        let res = eval.evaluate_all("5 x");
+
+        assert!(res.len() == 2);
+        let (first, first_out) = res[0].as_ref().unwrap();
+        assert!(first.0 .0 == 5 && first.1.is_some() && first_out.is_empty());
+        let (second, second_out) = res[1].as_ref().unwrap();
+        assert!(second.0 .0 == 5 && second.1.is_none() && *second_out == " x");
+    }
+
+    #[test]
+    fn nondeterministic() {
+        use crate::parser::IterParser;
+
+        // Matches the first time, and never again.
+        struct Once(std::cell::Cell<bool>);
+
+        impl IterParser for Once {
+            type Extract = ();
+            type ParserState = ();
+
+            #[allow(clippy::type_complexity)]
+            fn parse<'p>(
+                &self,
+                _state: Self::ParserState,
+                input: &'p str,
+            ) -> (
+                anyhow::Result<(Self::Extract, &'p str)>,
+                Option<Self::ParserState>,
+            ) {
+                if self.0.replace(false) {
+                    (Ok(((), input)), None)
+                } else {
+                    (Err(anyhow::anyhow!("Already matched")), None)
+                }
+            }
+
+            fn regex(&self) -> String {
+                String::new()
+            }
+        }
+
+        let and = crate::parser::And {
+            a: Once(std::cell::Cell::new(true)),
+            b: crate::parser::Opt {
+                parser: crate::parser::Literal::new(String::from("x")),
+            },
+        };
+
+        let eval = crate::parser::Evaluator::new(&and);
+        let res = eval.evaluate_all("x");
+
+        assert!(res.len() == 2);
+        assert!(res[0].is_ok());
+        assert!(res[1].is_err());
+    }
 }
@@ -1,14 +1,94 @@
-use super::IterParser;
+use super::{IterParser, Separator, Suggestion};
+use crate::brigadier::NodeKind;
+use crate::feedback::Message;
 use anyhow::{anyhow, Result};
 
-/// A literal should not have leading or trailing whitespaces.
+/// Folds the case of one character the way [`Literal`] compares them. Characters that lowercase to more
+/// than one character, like 'İ', are kept as they are.
+pub(crate) fn fold_char(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(folded), None) => folded,
+        _ => c,
+    }
+}
+
+/// Folds the case of a literal or input word character by character, so it keeps its length in characters.
+pub(crate) fn fold_case(value: &str) -> String {
+    value.chars().map(fold_char).collect()
+}
+
+/// How a [`Literal`] compares the input with its value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LiteralMode {
+    /// The input has to be the literal, letter for letter.
+    CaseSensitive,
+    /// The input may use any case, so `/TP` is `/tp`.
+    #[default]
+    CaseInsensitive,
+    /// Like [`LiteralMode::CaseInsensitive`], and every word may also be shortened to a prefix, so
+    /// `/gam` is `/gamemode`. A prefix keeps at least one character after a leading `/`. The
+    /// dispatcher rejects prefixes that more than one literal starts with.
+    Prefix,
+}
+
+/// A literal should not have leading or trailing whitespaces. Its words are written with single spaces,
+/// which stand for whatever [`Separator`] the literal is given.
 pub struct Literal {
     pub(crate) value: String,
+    pub(crate) mode: LiteralMode,
+    pub(crate) separator: Separator,
 }
 
 impl Literal {
     pub fn new(value: String) -> Self {
-        Self { value }
+        Self {
+            value,
+            mode: LiteralMode::default(),
+            separator: Separator::default(),
+        }
+    }
+
+    pub fn with_mode(self, mode: LiteralMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    pub fn mode(&self) -> LiteralMode {
+        self.mode
+    }
+
+    fn same_char(&self, literal: char, input: char) -> bool {
+        literal == input
+            || self.mode != LiteralMode::CaseSensitive && fold_char(literal) == fold_char(input)
+    }
+
+    /// Matches a word of the literal against the start of the input, and returns the rest of the input
+    /// and if the whole word was there.
+    fn parse_word<'i>(&self, word: &str, input: &'i str) -> Result<(&'i str, bool)> {
+        let mut chars = word.chars().peekable();
+        let mut matched = 0;
+        let mut end = 0;
+        for (i, inp_c) in input.char_indices() {
+            match chars.peek() {
+                Some(&lit_c) if self.same_char(lit_c, inp_c) => {
+                    chars.next();
+                    matched += 1;
+                    end = i + inp_c.len_utf8();
+                }
+                _ => break,
+            }
+        }
+
+        let complete = chars.peek().is_none();
+        let shortest = if word.starts_with('/') { 2 } else { 1 };
+        if complete || self.mode == LiteralMode::Prefix && matched >= shortest {
+            Ok((&input[end..], complete))
+        } else {
+            Err(anyhow!(Message::translatable(
+                "command.unknown.argument",
+                vec![]
+            )))
+        }
     }
 }
 
@@ -22,58 +102,117 @@ impl IterParser for Literal {
         _state: Self::ParserState,
         input: &'i str,
     ) -> (Result<(Self::Extract, &'i str)>, Option<Self::ParserState>) {
-        let input_lower = &mut input
-            .trim_start()
-            .chars()
-            .flat_map(|c| c.to_lowercase())
-            .peekable();
-        let literal_lower = &mut self.value.chars().flat_map(|c| c.to_lowercase()).peekable();
-        let mut ofsett = 0;
-
-        loop {
-            match (literal_lower.next(), input_lower.next()) {
-                (None, None) => {
-                    // Then the two str had the same length and were identical up to that point
-                    return (Ok(((), &"")), None);
-                }
-                (None, Some(' ')) => {
-                    // We have reached the end of the literal, and the next input char is a space
-                    return (Ok(((), &input[ofsett..])), None);
-                }
-                (None, Some(c)) => {
-                    // We have reached the end of the literal, and the next input char is not a space.
-                    return (
-                        Err(anyhow!(
-                            "Next char:'{}' was not whitespace or 'end of str' after literal.",
-                            c
-                        )),
-                        None,
-                    );
-                }
-                (Some(_), None) => {
-                    return (
-                        Err(anyhow!(
-                            "The input: \"{}\" is shorter then the literal: \"{}\"",
-                            &input,
-                            self.value.as_str()
-                        )),
-                        None,
-                    );
-                }
-                (Some(lit_c), Some(inp_c)) => {
-                    if lit_c == inp_c {
-                        ofsett += lit_c.len_utf8();
-                        continue;
-                    } else {
-                        return (Err(anyhow!("Literal did not match input")), None);
+        let mut rest = self.separator.skip(input);
+        let mut complete = true;
+        for (i, word) in self.value.split(' ').enumerate() {
+            if i > 0 {
+                rest = match self.separator.strip(rest) {
+                    Some(rest) => rest,
+                    None => {
+                        let key = if complete {
+                            "command.expected.separator"
+                        } else {
+                            "command.unknown.argument"
+                        };
+                        return (Err(anyhow!(Message::translatable(key, vec![]))), None);
                     }
+                };
+            }
+            match self.parse_word(word, rest) {
+                Ok((out, word_complete)) => {
+                    rest = out;
+                    complete = word_complete;
                 }
+                Err(err) => return (Err(err), None),
             }
         }
+
+        if self.separator.is_boundary(rest) {
+            (Ok(((), rest)), None)
+        } else if complete {
+            // The literal is there, but the input goes on without a separator.
+            (
+                Err(anyhow!(Message::translatable(
+                    "command.expected.separator",
+                    vec![]
+                ))),
+                None,
+            )
+        } else {
+            (
+                Err(anyhow!(Message::translatable(
+                    "command.unknown.argument",
+                    vec![]
+                ))),
+                None,
+            )
+        }
     }
 
     fn regex(&self) -> String {
-        regex_syntax::escape(self.value.as_str())
+        let words = self.value.split(' ');
+        let regex = match self.mode {
+            LiteralMode::CaseSensitive | LiteralMode::CaseInsensitive => {
+                words.map(regex_syntax::escape).collect::<Vec<_>>()
+            }
+            LiteralMode::Prefix => words
+                .map(|word| {
+                    // `/gamemode` becomes `/g(?:a(?:m(?:e...)?)?)?`.
+                    let shortest = if word.starts_with('/') { 2 } else { 1 };
+                    let split = word
+                        .char_indices()
+                        .nth(shortest)
+                        .map_or(word.len(), |(i, _)| i);
+                    let mut regex = regex_syntax::escape(&word[..split]);
+                    let optional: Vec<char> = word[split..].chars().collect();
+                    for c in &optional {
+                        regex.push_str("(?:");
+                        regex.push_str(&regex_syntax::escape(&c.to_string()));
+                    }
+                    regex.push_str(&")?".repeat(optional.len()));
+                    regex
+                })
+                .collect(),
+        };
+        let regex = regex.join(&self.separator.regex());
+        match self.mode {
+            LiteralMode::CaseSensitive => regex,
+            LiteralMode::CaseInsensitive | LiteralMode::Prefix => format!("(?i:{})", regex),
+        }
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![self
+            .value
+            .split(' ')
+            .map(|word| NodeKind::Literal(String::from(word)))
+            .collect()]
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.separator = separator.clone();
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        let partial = self.separator.skip(input);
+        let text = self
+            .value
+            .replace(' ', &self.separator.canonical().to_string());
+        let matches = match self.mode {
+            LiteralMode::CaseSensitive => text.starts_with(partial),
+            LiteralMode::CaseInsensitive | LiteralMode::Prefix => {
+                fold_case(&text).starts_with(&fold_case(partial))
+            }
+        };
+
+        if matches {
+            vec![Suggestion {
+                start: input.len() - partial.len(),
+                text,
+            }]
+        } else {
+            Vec::new()
+        }
     }
 }
 
@@ -85,9 +224,7 @@ mod tests {
 
     #[test]
     fn simple() {
-        let lit = Literal {
-            value: String::from("tp"),
-        };
+        let lit = Literal::new(String::from("tp"));
 
         let input = &mut "tp 10 10 10";
 
@@ -101,9 +238,7 @@ mod tests {
 
     #[test]
     fn empty() {
-        let lit = Literal {
-            value: String::from("tp"),
-        };
+        let lit = Literal::new(String::from("tp"));
 
         let input = "";
 
@@ -116,9 +251,7 @@ mod tests {
 
     #[test]
     fn partial() {
-        let lit = Literal {
-            value: String::from("tp"),
-        };
+        let lit = Literal::new(String::from("tp"));
 
         let input = "tpme";
 
@@ -132,15 +265,61 @@ mod tests {
 
     #[test]
     fn case() {
-        let lit = Literal {
-            value: String::from("tp"),
-        };
+        let lit = Literal::new(String::from("tp"));
 
         let input = "tp me";
 
         let eval = Evaluator::new(&lit);
         let res = eval.evaluate_all(input);
         assert!(res.len() == 1);
-        assert!(res.get(0).unwrap().as_ref().unwrap().1 == " me");
+        assert!(res.first().unwrap().as_ref().unwrap().1 == " me");
+    }
+
+    #[test]
+    fn modes() {
+        let ignore_case = Literal::new(String::from("/time set"));
+        assert_eq!(ignore_case.parse((), "/TIME Set 1").0.unwrap().1, " 1");
+        assert_eq!(ignore_case.regex(), "(?i:/time\\s+set)");
+
+        let exact = Literal::new(String::from("/tp")).with_mode(LiteralMode::CaseSensitive);
+        assert!(exact.parse((), "/TP").0.is_err());
+        assert!(exact.parse((), "/tp\t@s").0.is_ok());
+        assert_eq!(exact.regex(), "/tp");
+
+        let prefix = Literal::new(String::from("/time set")).with_mode(LiteralMode::Prefix);
+        assert_eq!(prefix.parse((), "/ti s 1").0.unwrap().1, " 1");
+        assert_eq!(prefix.parse((), "/Time Set").0.unwrap().1, "");
+        assert!(prefix.parse((), "/ 1").0.is_err());
+        assert!(prefix.parse((), "/tix").0.is_err());
+        assert!(prefix.parse((), "/timex set").0.is_err());
+        assert!(ignore_case.parse((), "/tim set").0.is_err());
+        assert_eq!(prefix.regex(), "(?i:/t(?:i(?:m(?:e)?)?)?\\s+s(?:e(?:t)?)?)");
+    }
+
+    #[test]
+    fn brigadier() {
+        let literal = |value: &str| Literal::new(String::from(value)).brigadier();
+        assert_eq!(
+            literal("/time set"),
+            vec![vec![
+                NodeKind::Literal(String::from("/time")),
+                NodeKind::Literal(String::from("set"))
+            ]]
+        );
+        // Only spaces separate words, like when the literal is parsed.
+        assert_eq!(
+            literal("/a\tb"),
+            vec![vec![NodeKind::Literal(String::from("/a\tb"))]]
+        );
+    }
+
+    #[test]
+    fn fold() {
+        assert_eq!(fold_case("/TiME"), "/time");
+        // 'İ' lowercases to "i̇", which would make it a prefix of words starting with 'i'.
+        assert_eq!(fold_case("İ"), "İ");
+        let prefix = Literal::new(String::from("/item")).with_mode(LiteralMode::Prefix);
+        assert!(prefix.parse((), "/İ").0.is_err());
+        assert!(!fold_case("/item").starts_with(&fold_case("/İ")));
     }
 }
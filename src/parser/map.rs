@@ -1,20 +1,14 @@
-use crate::generic::Func;
-use crate::parser::IterParser;
+use crate::argument::Registries;
+use crate::parser::{IterParser, Separator, Suggestion};
+use crate::{brigadier::NodeKind, generic::Func};
 
 pub struct Map<P, F> {
     pub(crate) parser: P,
     pub(crate) map: F,
 }
-#[derive(Debug)]
-pub struct MapState<S> {
-    state: S,
-}
-
-impl<S: Default> Default for MapState<S> {
-    fn default() -> Self {
-        MapState {
-            state: S::default(),
-        }
+impl<P, F> Map<P, F> {
+    pub fn new(parser: P, map: F) -> Self {
+        Self { parser, map }
     }
 }
 
@@ -35,7 +29,20 @@ where
         anyhow::Result<(Self::Extract, &'p str), anyhow::Error>,
         Option<Self::ParserState>,
     ) {
-        let (result, state) = self.parser.parse(state, input);
+        self.parse_in(&(), state, input)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn parse_in<'p>(
+        &self,
+        registries: &dyn Registries,
+        state: Self::ParserState,
+        input: &'p str,
+    ) -> (
+        anyhow::Result<(Self::Extract, &'p str), anyhow::Error>,
+        Option<Self::ParserState>,
+    ) {
+        let (result, state) = self.parser.parse_in(registries, state, input);
 
         match result {
             Ok((ext, out)) => (Ok(((self.map.call(ext),), out)), state),
@@ -45,4 +52,20 @@ where
     fn regex(&self) -> String {
         self.parser.regex()
     }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        self.parser.brigadier()
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        self.parser.suggest(input)
+    }
+
+    fn suggest_in(&self, registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        self.parser.suggest_in(registries, input)
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.parser.set_separator(separator)
+    }
 }
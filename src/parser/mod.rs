@@ -13,7 +13,72 @@ pub use map::*;
 pub use optional::*;
 pub use space::*;
 
-use crate::generic::Tuple;
+use crate::{
+    argument::Registries,
+    brigadier::{ArgumentType, NodeKind, StringKind},
+    generic::Tuple,
+};
+
+/// A way to complete the end of some input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Suggestion {
+    /// Byte offset into the input where the suggested text starts. Everything after it is replaced by `text`.
+    pub start: usize,
+    pub text: String,
+}
+
+impl Suggestion {
+    pub(crate) fn shifted(self, offset: usize) -> Self {
+        Self {
+            start: self.start + offset,
+            text: self.text,
+        }
+    }
+}
+
+/// A parse error with the byte offset into the input of the parser that failed. [`And`] wraps the errors of
+/// its right parser in it, so commands can report the error that got furthest into the input. It shows as
+/// the error it wraps, and commands unwrap it again before they return the error.
+#[derive(Debug)]
+pub(crate) struct Located {
+    pub(crate) position: usize,
+    pub(crate) error: anyhow::Error,
+}
+
+impl std::fmt::Display for Located {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for Located {}
+
+impl Located {
+    /// Moves an error of a parser that started `offset` bytes into the input.
+    pub(crate) fn shift(error: anyhow::Error, offset: usize) -> anyhow::Error {
+        if offset == 0 {
+            return error;
+        }
+        match error.downcast::<Located>() {
+            Ok(located) => anyhow::Error::new(Located {
+                position: located.position + offset,
+                error: located.error,
+            }),
+            Err(error) => anyhow::Error::new(Located {
+                position: offset,
+                error,
+            }),
+        }
+    }
+
+    /// Splits an error into where it happened and the error itself.
+    pub(crate) fn split(error: anyhow::Error) -> (usize, anyhow::Error) {
+        match error.downcast::<Located>() {
+            Ok(located) => (located.position, located.error),
+            Err(error) => (0, error),
+        }
+    }
+}
 
 pub trait IterParser {
     /// This assosiated type says what the return value is for the parser. If you have a parser that returns a i32, then set it to Extract = (i32,), or
@@ -23,12 +88,18 @@ pub trait IterParser {
     /// You probably just want to set it to (), and always return None in its place for 'fn parse(&self ...'
     // Internaly we use this for the And, Or and the Optional parsers. This makes implementors of the Parser trait
     // generators of potential parsing results.
-    type ParserState: Default;
+    // Cloning a state should be cheap. The And parser clones the state of its left parser, so it can replay
+    // a match instead of cloning the extract.
+    type ParserState: Default + Clone;
 
     /// State is set to default at first call, and is thereafter the state that was returned on the last call.
     /// this makes it so we can iterate over many possible attempts at parsin the input.
     /// We have to do this becaue else its impossible to correctly parse (Option<u32>,  u32) from the input "42".
     /// we first need to try parsing were the option consumes the 42, and then we need to try the case were it does not.
+    ///
+    /// Parsing must be deterministic: parsing the same input from clones of the same state has to give the same
+    /// result every time. [`And`] relies on this to parse its left parser again instead of cloning the extract,
+    /// and reports an error if a parser matches differently the second time.
     #[allow(clippy::type_complexity)]
     fn parse<'p>(
         &self,
@@ -39,6 +110,22 @@ pub trait IterParser {
         Option<Self::ParserState>,
     );
 
+    /// Like [`IterParser::parse`], with the registries of the game to check ids against. The dispatcher parses
+    /// commands through this, so parsers that contain other parsers have to pass the registries on.
+    /// Parsers that never look at registries can leave this to call [`IterParser::parse`].
+    #[allow(clippy::type_complexity)]
+    fn parse_in<'p>(
+        &self,
+        _registries: &dyn Registries,
+        state: Self::ParserState,
+        input: &'p str,
+    ) -> (
+        anyhow::Result<(Self::Extract, &'p str)>,
+        Option<Self::ParserState>,
+    ) {
+        self.parse(state, input)
+    }
+
     /// This method should return a regex that recognises a language that is a superset of what the parser recognises.
     /// Another way to put it. If the parser sucsessfully parses some input, then the regex should have matched the part
     /// that it consumed, but it does not have to be the other way arround. Theoretically we could therefor always use '.*?'
@@ -47,6 +134,32 @@ pub trait IterParser {
     /// to be able to parse json then a suitable regex could be "\{.*?\}". Using this regex we can quickly determine what command
     /// a input belongs to.
     fn regex(&self) -> String;
+
+    /// Returns ways to complete the input, for when the input ends in the middle of what this parser parses.
+    /// Parsers that have nothing sensible to suggest, like numbers, can leave this empty.
+    fn suggest(&self, _input: &str) -> Vec<Suggestion> {
+        Vec::new()
+    }
+
+    /// Like [`IterParser::suggest`], with the registries of the game to suggest ids from, see [`IterParser::parse_in`].
+    fn suggest_in(&self, _registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        self.suggest(input)
+    }
+
+    /// Describes the parser as brigadier nodes, see [`crate::brigadier`]. Every inner `Vec` is one sequence of
+    /// nodes the parser accepts, so optional parts give more than one, and separators like spaces give none.
+    /// Parsers that brigadier has no equivalent for fall back to a greedy string, which lets the client send anything.
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![vec![NodeKind::argument(
+            "args",
+            ArgumentType::String(StringKind::GreedyPhrase),
+        )]]
+    }
+
+    /// Makes the parser separate words with `separator`. The dispatcher calls this on every command it
+    /// registers, so parsers that contain other parsers have to pass it on. Parsers that never look at
+    /// separators can leave this empty.
+    fn set_separator(&mut self, _separator: &Separator) {}
 }
 
 // This feature cant be implemented before rust gets an upgrade.
@@ -1,17 +1,23 @@
-use crate::generic::Tuple;
+use crate::{argument::Registries, brigadier::NodeKind, generic::Tuple};
 
-use super::IterParser;
+use super::{IterParser, Separator, Suggestion};
 
 pub struct Opt<P> {
     pub(crate) parser: P,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum OptState<State> {
     Consume(State),
     Skip(),
 }
 
+impl<P> Opt<P> {
+    pub fn new(parser: P) -> Self {
+        Self { parser }
+    }
+}
+
 impl<State: Default> Default for OptState<State> {
     fn default() -> Self {
         Self::Consume(State::default())
@@ -21,7 +27,6 @@ impl<State: Default> Default for OptState<State> {
 impl<P: IterParser> IterParser for Opt<P>
 where
     P::Extract: Tuple,
-    P::ParserState: Default,
 {
     type Extract = (Option<P::Extract>,);
     type ParserState = OptState<P::ParserState>;
@@ -34,6 +39,19 @@ where
     ) -> (
         anyhow::Result<(Self::Extract, &'p str)>,
         Option<Self::ParserState>,
+    ) {
+        self.parse_in(&(), state, input)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn parse_in<'p>(
+        &self,
+        registries: &dyn Registries,
+        state: Self::ParserState,
+        input: &'p str,
+    ) -> (
+        anyhow::Result<(Self::Extract, &'p str)>,
+        Option<Self::ParserState>,
     ) {
         match state {
             OptState::Skip() => {
@@ -43,7 +61,8 @@ where
             }
             OptState::Consume(sub_parser_state) => {
                 // We parse the input trying to consume the start.
-                let (res, sub_parser_state) = self.parser.parse(sub_parser_state, input);
+                let (res, sub_parser_state) =
+                    self.parser.parse_in(registries, sub_parser_state, input);
 
                 match (res, sub_parser_state) {
                     (Ok((ext, out)), None) => {
@@ -62,6 +81,24 @@ where
     fn regex(&self) -> String {
         format!("({})?", self.parser.regex())
     }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        let mut paths = self.parser.brigadier();
+        paths.push(Vec::new());
+        paths
+    }
+
+    fn suggest(&self, input: &str) -> Vec<Suggestion> {
+        self.parser.suggest(input)
+    }
+
+    fn suggest_in(&self, registries: &dyn Registries, input: &str) -> Vec<Suggestion> {
+        self.parser.suggest_in(registries, input)
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.parser.set_separator(separator)
+    }
 }
 
 #[cfg(test)]
@@ -73,9 +110,7 @@ mod tests {
 
     #[test]
     fn simple1() {
-        let lit = Literal {
-            value: String::from("tp"),
-        };
+        let lit = Literal::new(String::from("tp"));
 
         let opt = Opt { parser: lit };
 
@@ -91,9 +126,7 @@ mod tests {
 
     #[test]
     fn simple2() {
-        let lit = Literal {
-            value: String::from("tp"),
-        };
+        let lit = Literal::new(String::from("tp"));
 
         let opt = Opt { parser: lit };
 
@@ -109,9 +142,7 @@ mod tests {
 
     #[test]
     fn empty() {
-        let lit = Literal {
-            value: String::from("tp"),
-        };
+        let lit = Literal::new(String::from("tp"));
 
         let opt = Opt { parser: lit };
 
@@ -120,16 +151,14 @@ mod tests {
         let eval = Evaluator::new(&opt);
 
         let res = eval.evaluate_all(input);
-        assert!(res.get(0).unwrap().is_err());
+        assert!(res.first().unwrap().is_err());
         assert!(res.get(1).unwrap().is_ok());
         assert!(res.len() == 2);
     }
 
     #[test]
     fn partial() {
-        let lit = Literal {
-            value: String::from("tp"),
-        };
+        let lit = Literal::new(String::from("tp"));
 
         let input = "tpme";
 
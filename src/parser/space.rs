@@ -1,41 +1,150 @@
 use super::IterParser;
-use anyhow::anyhow;
+use crate::brigadier::NodeKind;
+use crate::feedback::Message;
+use anyhow::{anyhow, bail};
 
-#[derive(Debug)]
+#[derive(Debug, Default, Clone)]
 pub enum OnceState {
+    #[default]
     More,
     Done,
 }
-impl Default for OnceState {
-    fn default() -> Self {
-        OnceState::More
+
+/// How the words of a command are separated. Literals, spaces, keywords, coordinates, entity selectors
+/// and resource locations follow the separator of the dispatcher they are registered with, see
+/// [`IterParser::set_separator`]. The other built-in parsers end where their value ends, so they only
+/// need a separator after them if the next parser expects one.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Separator {
+    /// Exactly one space, like vanilla.
+    Space,
+    /// Any amount of whitespace.
+    #[default]
+    Whitespace,
+    /// Any amount of the given characters, like `,` or `\t`. There has to be at least one, see [`Separator::chars`].
+    Chars(Vec<char>),
+}
+
+impl Separator {
+    /// A [`Separator::Chars`], or an error if there are no characters that separate words.
+    pub fn chars(chars: impl IntoIterator<Item = char>) -> anyhow::Result<Self> {
+        let separator = Separator::Chars(chars.into_iter().collect());
+        separator.validate()?;
+        Ok(separator)
+    }
+
+    /// Returns an error if the separator can not separate anything, which is the case for an empty [`Separator::Chars`].
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Separator::Chars(chars) if chars.is_empty() => {
+                bail!("A separator needs at least one character")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_separator(&self, c: char) -> bool {
+        match self {
+            Separator::Space => c == ' ',
+            Separator::Whitespace => c.is_whitespace(),
+            Separator::Chars(chars) => chars.contains(&c),
+        }
+    }
+
+    /// The separator that is written between words, for example in suggestions.
+    pub fn canonical(&self) -> char {
+        match self {
+            Separator::Space | Separator::Whitespace => ' ',
+            Separator::Chars(chars) => chars.first().copied().unwrap_or(' '),
+        }
+    }
+
+    /// Removes one separator from the start of the input, or returns `None` if there is none.
+    pub fn strip<'i>(&self, input: &'i str) -> Option<&'i str> {
+        let out = match self {
+            Separator::Space => input.strip_prefix(' ')?,
+            _ => input.trim_start_matches(|c| self.is_separator(c)),
+        };
+        if out.len() == input.len() {
+            None
+        } else {
+            Some(out)
+        }
+    }
+
+    /// Removes a separator from the start of the input if there is one.
+    pub fn skip<'i>(&self, input: &'i str) -> &'i str {
+        self.strip(input).unwrap_or(input)
+    }
+
+    /// Returns true if a word can end right before the input.
+    pub fn is_boundary(&self, input: &str) -> bool {
+        input.chars().next().is_none_or(|c| self.is_separator(c))
+    }
+
+    /// The words of the input, without empty ones between repeated separators.
+    pub fn words<'a>(&'a self, input: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        input
+            .split(move |c| self.is_separator(c))
+            .filter(|word| !word.is_empty())
+    }
+
+    /// A regex that matches one separator, see [`Separator::strip`].
+    pub fn regex(&self) -> String {
+        match self {
+            Separator::Space => String::from(" "),
+            Separator::Whitespace => String::from("\\s+"),
+            Separator::Chars(_) => format!("[{}]+", self.class()),
+        }
+    }
+
+    /// The separator characters as the inside of a regex character class, so parsers can match
+    /// everything but a separator with `[^...]`.
+    pub(crate) fn class(&self) -> String {
+        match self {
+            Separator::Space => String::from(" "),
+            Separator::Whitespace => String::from("\\s"),
+            Separator::Chars(chars) => chars
+                .iter()
+                .map(|c| regex_syntax::escape(&c.to_string()))
+                .collect(),
+        }
     }
 }
 
-pub struct OneOrMoreSpace;
-pub struct MaybeSpaces;
+#[derive(Default)]
+pub struct OneOrMoreSpace {
+    separator: Separator,
+}
+
+#[derive(Default)]
+pub struct MaybeSpaces {
+    separator: Separator,
+    trailing: bool,
+}
 
 impl MaybeSpaces {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
     }
-}
 
-impl OneOrMoreSpace {
-    pub fn new() -> Self {
-        Self {}
+    /// The separators a command may end with, see [`CommandBuilder::on_call`](crate::command::builder::CommandBuilder::on_call).
+    /// Under [`Separator::Space`] there are none, so like in vanilla `/time set 1 ` is rejected.
+    pub fn trailing() -> Self {
+        Self {
+            trailing: true,
+            ..Self::default()
+        }
     }
-}
 
-impl Default for OneOrMoreSpace {
-    fn default() -> Self {
-        Self::new()
+    fn disabled(&self) -> bool {
+        self.trailing && self.separator == Separator::Space
     }
 }
 
-impl Default for MaybeSpaces {
-    fn default() -> Self {
-        Self::new()
+impl OneOrMoreSpace {
+    pub fn new() -> Self {
+        Self::default()
     }
 }
 
@@ -53,17 +162,28 @@ impl IterParser for OneOrMoreSpace {
         anyhow::Result<(Self::Extract, &'p str)>,
         Option<Self::ParserState>,
     ) {
-        let before_len = input.len();
-        let out = input.trim_start();
-        if out.len() == before_len {
-            (Err(anyhow!("Expected a space")), None)
-        } else {
-            (Ok(((), out)), None)
+        match self.separator.strip(input) {
+            Some(out) => (Ok(((), out)), None),
+            None => (
+                Err(anyhow!(Message::translatable(
+                    "command.expected.separator",
+                    vec![]
+                ))),
+                None,
+            ),
         }
     }
 
     fn regex(&self) -> String {
-        "\\s+".to_string()
+        self.separator.regex()
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![Vec::new()]
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.separator = separator.clone();
     }
 }
 
@@ -80,11 +200,25 @@ impl IterParser for MaybeSpaces {
         anyhow::Result<(Self::Extract, &'p str)>,
         Option<Self::ParserState>,
     ) {
-        let out = input.trim_start();
-        (Ok(((), out)), None)
+        if self.disabled() {
+            return (Ok(((), input)), None);
+        }
+        (Ok(((), self.separator.skip(input))), None)
     }
 
     fn regex(&self) -> String {
-        "\\s*".to_string()
+        match &self.separator {
+            _ if self.disabled() => String::new(),
+            Separator::Whitespace => String::from("\\s*"),
+            separator => format!("({})?", separator.regex()),
+        }
+    }
+
+    fn brigadier(&self) -> Vec<Vec<NodeKind>> {
+        vec![Vec::new()]
+    }
+
+    fn set_separator(&mut self, separator: &Separator) {
+        self.separator = separator.clone();
     }
 }
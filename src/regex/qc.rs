@@ -24,13 +24,7 @@ pub enum NfaQt {
 impl NfaQt {
     fn new(g: &mut quickcheck::Gen, level: usize) -> Self {
         if level == 0 {
-            return NfaQt::Lit(
-                String::arbitrary(g)
-                    .chars()
-                    .into_iter()
-                    .take(MAX_LIT_LEN)
-                    .collect(),
-            );
+            return NfaQt::Lit(String::arbitrary(g).chars().take(MAX_LIT_LEN).collect());
         }
 
         let choice = g.choose(&[0, 1]).unwrap();
@@ -94,6 +88,7 @@ impl NfaQt {
 #[cfg(test)]
 #[derive(Debug, Clone)]
 pub struct NFAQtCase {
+    #[allow(dead_code)]
     pub kind: NfaQt,
     pub matches: HashSet<String>,
     pub nfa: NFA<usize>,
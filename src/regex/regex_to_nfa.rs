@@ -92,7 +92,8 @@ fn hir_to_nfa<A: std::hash::Hash + Eq + Copy + std::fmt::Debug>(
         regex_syntax::hir::HirKind::Repetition(x) => {
             match &x.kind {
                 regex_syntax::hir::RepetitionKind::ZeroOrOne => {
-                    let nfa = NFA::<A>::empty();
+                    // NFA::empty() matches nothing, so the skip branch has to be the empty literal.
+                    let nfa = NFA::<A>::literal("");
                     nfa.or(hir_to_nfa(&x.hir)?)
                 }
                 regex_syntax::hir::RepetitionKind::ZeroOrMore => hir_to_nfa(&x.hir)?.repeat(),
@@ -209,6 +210,7 @@ impl std::fmt::Debug for RegexConvertError {
 /// Checks if regex contains a feature we don't suport, or it just cant be parsed as
 /// valid regex. If this test passes for a command, then the only other failurecase for
 /// creating a nfa is running out of StateId: u32.
+#[allow(clippy::result_large_err)]
 pub fn we_suport_regex(regex: &str) -> Result<(), RegexConvertError> {
     let hir = Parser::new().parse(regex);
     let hir = match hir {
@@ -220,6 +222,7 @@ pub fn we_suport_regex(regex: &str) -> Result<(), RegexConvertError> {
 }
 
 /// Returns wheter or not we are able to parse regex. We dont suport certain features.
+#[allow(clippy::result_large_err)]
 fn we_suport_hir(hir: &regex_syntax::hir::Hir) -> Result<(), RegexConvertError> {
     match hir.kind() {
         regex_syntax::hir::HirKind::Empty => Ok(()),
@@ -268,17 +271,13 @@ fn we_suport_hir(hir: &regex_syntax::hir::Hir) -> Result<(), RegexConvertError>
         },
         regex_syntax::hir::HirKind::Concat(cats) => {
             for meow in cats {
-                if let Err(x) = we_suport_hir(meow) {
-                    return Err(x);
-                }
+                we_suport_hir(meow)?;
             }
             Ok(())
         }
         regex_syntax::hir::HirKind::Alternation(alts) => {
             for alt in alts {
-                if let Err(x) = we_suport_hir(alt) {
-                    return Err(x);
-                }
+                we_suport_hir(alt)?;
             }
             Ok(())
         }
@@ -334,7 +333,18 @@ mod tests {
             assert!(dfa.find(case).is_ok());
         }
 
-        for case in &["fu"] {
+        assert!(dfa.find("fu").is_err());
+    }
+
+    #[test]
+    fn zero_or_one() {
+        let nfa = NFA::<usize>::regex("[\\+-]?\\d+").unwrap();
+        let dfa = DFA::<usize>::from(nfa);
+
+        for case in &["1", "+1", "-10"] {
+            assert!(dfa.find(case).is_ok());
+        }
+        for case in &["", "+", "+-1"] {
             assert!(dfa.find(case).is_err());
         }
     }
@@ -347,9 +357,7 @@ mod tests {
         for case in &["1", "2", "3", "4", "5", "6", "7", "8", "9", "0"] {
             assert!(dfa.find(case).is_ok());
         }
-        for case in &["a"] {
-            assert!(dfa.find(case).is_err());
-        }
+        assert!(dfa.find("a").is_err());
     }
 
     #[test]
@@ -980,7 +980,7 @@ mod tests {
     //     }
     // }
 
-    #[test]
+    // #[test]
     // fn test_failing() {
     //     let from = '𿾿';
     //     let to = '򟘈';
@@ -1107,7 +1107,7 @@ mod tests {
                                 "ERROR from:{} to:{} value:{} should be accepted",
                                 from, to, value
                             );
-                            panic!(false);
+                            panic!();
                         }
                     } else if dfa.find(&string).is_ok() {
                         let mut from_buff = [0u8; 4];
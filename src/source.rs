@@ -0,0 +1,222 @@
+//! Who runs a command and from where, which handlers resolve relative coordinates and `@s` against.
+
+use std::marker::PhantomData;
+
+use crate::argument::{CoordinateSource, Registries, ResourceLocation};
+use crate::command::GameStateFamily;
+
+/// Who ran a command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Executor {
+    Player { name: String, uuid: u128 },
+    Console,
+    CommandBlock { position: [i32; 3] },
+    Function(ResourceLocation),
+}
+
+/// The context a command runs in. `/execute` can change everything except who ran the command, so a
+/// command run `as` another entity still reports back to the player who typed it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandSource {
+    pub executor: Executor,
+    /// The entity the command runs as, which `@s` selects.
+    pub entity: Option<u128>,
+    pub position: [f64; 3],
+    /// Yaw and pitch in degrees.
+    pub rotation: [f32; 2],
+    pub world: ResourceLocation,
+    /// From 0 for regular players to 4 for the console.
+    pub permission_level: u8,
+}
+
+impl CommandSource {
+    pub const MAX_PERMISSION_LEVEL: u8 = 4;
+
+    /// A source in the overworld at the origin, with no entity and no permissions.
+    pub fn new(executor: Executor) -> Self {
+        let entity = match &executor {
+            Executor::Player { uuid, .. } => Some(*uuid),
+            _ => None,
+        };
+        Self {
+            executor,
+            entity,
+            position: [0.0; 3],
+            rotation: [0.0; 2],
+            world: ResourceLocation::minecraft("overworld").unwrap(),
+            permission_level: 0,
+        }
+    }
+
+    pub fn console() -> Self {
+        Self::new(Executor::Console).with_permission_level(Self::MAX_PERMISSION_LEVEL)
+    }
+
+    pub fn player(name: &str, uuid: u128) -> Self {
+        Self::new(Executor::Player {
+            name: String::from(name),
+            uuid,
+        })
+    }
+
+    pub fn with_entity(self, entity: Option<u128>) -> Self {
+        Self { entity, ..self }
+    }
+
+    pub fn with_position(self, position: [f64; 3]) -> Self {
+        Self { position, ..self }
+    }
+
+    pub fn with_rotation(self, rotation: [f32; 2]) -> Self {
+        Self { rotation, ..self }
+    }
+
+    pub fn with_world(self, world: ResourceLocation) -> Self {
+        Self { world, ..self }
+    }
+
+    pub fn with_permission_level(self, permission_level: u8) -> Self {
+        Self {
+            permission_level,
+            ..self
+        }
+    }
+
+    pub fn has_permission(&self, level: u8) -> bool {
+        self.permission_level >= level
+    }
+
+    /// The name vanilla uses for the executor in messages.
+    pub fn name(&self) -> String {
+        match &self.executor {
+            Executor::Player { name, .. } => name.clone(),
+            Executor::Console => String::from("Server"),
+            Executor::CommandBlock { .. } => String::from("@"),
+            Executor::Function(function) => function.to_string(),
+        }
+    }
+}
+
+impl CoordinateSource for CommandSource {
+    fn position(&self) -> [f64; 3] {
+        self.position
+    }
+
+    fn rotation(&self) -> [f32; 2] {
+        self.rotation
+    }
+}
+
+/// A [`GameStateFamily`] whose game states carry a [`CommandSource`], so the dispatcher can hand a
+/// command a different source than the one it was called with.
+pub trait SourceFamily: GameStateFamily {
+    fn source<'b>(gamestate: &'b Self::GameState<'_>) -> &'b CommandSource;
+
+    /// Reborrows the game state with `source` in place of its own source.
+    fn with_source<'b>(
+        gamestate: &'b mut Self::GameState<'_>,
+        source: &'b CommandSource,
+    ) -> Self::GameState<'b>;
+}
+
+/// The family of game states `(&'a mut T, &'a CommandSource)`. The game `T` has the registries the
+/// arguments are checked against.
+pub struct Sourced<T>(PhantomData<fn() -> T>);
+
+impl<T: Registries + 'static> GameStateFamily for Sourced<T> {
+    type GameState<'a> = (&'a mut T, &'a CommandSource);
+
+    fn reborrow<'b>(gamestate: &'b mut Self::GameState<'_>) -> Self::GameState<'b> {
+        (&mut *gamestate.0, gamestate.1)
+    }
+
+    fn registries<'b>(gamestate: &'b Self::GameState<'_>) -> &'b dyn Registries {
+        &*gamestate.0
+    }
+}
+
+impl<T: Registries + 'static> SourceFamily for Sourced<T> {
+    fn source<'b>(gamestate: &'b Self::GameState<'_>) -> &'b CommandSource {
+        gamestate.1
+    }
+
+    fn with_source<'b>(
+        gamestate: &'b mut Self::GameState<'_>,
+        source: &'b CommandSource,
+    ) -> Self::GameState<'b> {
+        (&mut *gamestate.0, source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::argument::{EntitySelector, Registries, Vec3};
+    use crate::command::builder::{literal, CommandBuilder};
+    use crate::dispatcher::Dispatcher;
+
+    use super::{CommandSource, Executor, SourceFamily, Sourced};
+
+    impl Registries for Vec<String> {}
+
+    #[test]
+    fn source() {
+        let console = CommandSource::console();
+        assert!(console.has_permission(4));
+        assert_eq!(console.entity, None);
+        assert_eq!(console.name(), "Server");
+
+        let player = CommandSource::player("Notch", 7);
+        assert_eq!(player.entity, Some(7));
+        assert!(!player.has_permission(2));
+        assert_eq!(player.world.to_string(), "minecraft:overworld");
+
+        let block = CommandSource::new(Executor::CommandBlock {
+            position: [1, 2, 3],
+        });
+        assert_eq!(block.name(), "@");
+    }
+
+    #[test]
+    fn dispatch() {
+        let mut dispatcher = Dispatcher::<Sourced<Vec<String>>, ()>::new();
+        dispatcher
+            .register(literal("/tp").space().arg::<Vec3>().on_call(|pos: Vec3| {
+                move |log: &mut Vec<String>, source: &CommandSource| {
+                    let [x, y, z] = pos.resolve(source);
+                    log.push(format!("{} {} {} {}", source.name(), x, y, z));
+                }
+            }))
+            .unwrap();
+        dispatcher
+            .register(literal("/kill").space().arg::<EntitySelector>().on_call(
+                |target: EntitySelector| {
+                    move |log: &mut Vec<String>, source: &CommandSource| {
+                        if target.is_executor() {
+                            log.push(format!("killed {:?}", source.entity));
+                        }
+                    }
+                },
+            ))
+            .unwrap();
+
+        let mut log = Vec::new();
+        let source = CommandSource::player("Notch", 7).with_position([10.0, 64.0, -3.0]);
+        dispatcher.call((&mut log, &source), "/tp ~ ~1 ~").unwrap();
+        dispatcher.call((&mut log, &source), "/kill @s").unwrap();
+
+        let forked = source.clone().with_entity(Some(9));
+        let mut gamestate = (&mut log, &source);
+        dispatcher
+            .call(
+                Sourced::<Vec<String>>::with_source(&mut gamestate, &forked),
+                "/kill @s",
+            )
+            .unwrap();
+        assert_eq!(Sourced::<Vec<String>>::source(&gamestate).entity, Some(7));
+
+        assert_eq!(
+            log,
+            vec!["Notch 10 65 -3", "killed Some(7)", "killed Some(9)"]
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! Turns [`Message`]s into text, so feedback and errors can be shown in the language of each player.
+
+use std::collections::HashMap;
+
+use crate::feedback::Message;
+
+/// Looks up the text of translation keys, like the language files of the game do.
+pub trait Translator {
+    /// The format of the message with the key, where `%s` and `%1$s` stand for its arguments.
+    fn format(&self, key: &str) -> Option<&str>;
+}
+
+/// A loaded language file, from translation key to format.
+impl Translator for HashMap<String, String> {
+    fn format(&self, key: &str) -> Option<&str> {
+        self.get(key).map(String::as_str)
+    }
+}
+
+/// The English texts of the messages lieutenant sends, using the keys and wording of vanilla.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct English;
+
+const ENGLISH: &[(&str, &str)] = &[
+    (
+        "command.unknown.command",
+        "Unknown or incomplete command, see below for error",
+    ),
+    ("command.unknown.argument", "Incorrect argument for command"),
+    (
+        "command.expected.separator",
+        "Expected whitespace to end one argument, but found trailing data",
+    ),
+    ("command.context.here", "<--[HERE]"),
+    ("lieutenant.command.suggestion", "Did you mean %s?"),
+    (
+        "lieutenant.command.ambiguous",
+        "Ambiguous command, %s could be %s",
+    ),
+    (
+        "lieutenant.command.redirects",
+        "A command can not be redirected more than %s times",
+    ),
+    (
+        "lieutenant.parser.nondeterministic",
+        "A parser matched differently when it was run again on the same input",
+    ),
+    (
+        "lieutenant.nbt.depth",
+        "NBT can not be nested more than %s levels deep",
+    ),
+    (
+        "lieutenant.time.tick_count_too_high",
+        "The tick count must not be more than %s, found %s",
+    ),
+    ("parsing.int.expected", "Expected integer"),
+    ("parsing.int.invalid", "Invalid integer '%s'"),
+    ("parsing.long.expected", "Expected long"),
+    ("parsing.float.expected", "Expected float"),
+    ("parsing.float.invalid", "Invalid float '%s'"),
+    ("parsing.double.expected", "Expected double"),
+    ("parsing.double.invalid", "Invalid double '%s'"),
+    ("parsing.bool.expected", "Expected boolean"),
+    (
+        "parsing.quote.expected.start",
+        "Expected quote to start a string",
+    ),
+    ("parsing.quote.expected.end", "Unclosed quoted string"),
+    (
+        "parsing.quote.escape",
+        "Invalid escape sequence '\\%s' in quoted string",
+    ),
+    ("parsing.expected", "Expected '%s'"),
+    (
+        "argument.integer.low",
+        "Integer must not be less than %s, found %s",
+    ),
+    (
+        "argument.integer.big",
+        "Integer must not be more than %s, found %s",
+    ),
+    ("argument.entity.notfound.entity", "No entity was found"),
+    ("argument.entity.notfound.player", "No player was found"),
+    ("argument.entity.invalid", "Invalid name or UUID"),
+    (
+        "argument.entity.toomany",
+        "Only one entity is allowed, but the provided selector allows more than one",
+    ),
+    (
+        "argument.player.toomany",
+        "Only one player is allowed, but the provided selector allows more than one",
+    ),
+    (
+        "argument.player.entities",
+        "Only players may be affected by this command, but the provided selector includes entities",
+    ),
+    ("argument.entity.selector.missing", "Missing selector type"),
+    (
+        "argument.entity.selector.unknown",
+        "Unknown selector type '%s'",
+    ),
+    ("argument.entity.options.unknown", "Unknown option '%s'"),
+    (
+        "argument.entity.options.valueless",
+        "Expected value for option '%s'",
+    ),
+    (
+        "argument.entity.options.inapplicable",
+        "Option '%s' isn't applicable here",
+    ),
+    (
+        "argument.entity.options.unterminated",
+        "Expected end of options",
+    ),
+    (
+        "argument.entity.options.distance.negative",
+        "Distance cannot be negative",
+    ),
+    (
+        "argument.entity.options.level.negative",
+        "Level shouldn't be negative",
+    ),
+    (
+        "argument.entity.options.limit.toosmall",
+        "Limit must be at least 1",
+    ),
+    (
+        "argument.entity.options.sort.irreversible",
+        "Invalid or unknown sort type '%s'",
+    ),
+    (
+        "argument.entity.options.mode.invalid",
+        "Invalid or unknown game mode '%s'",
+    ),
+    ("argument.pos.missing.double", "Expected a coordinate"),
+    (
+        "argument.pos2d.incomplete",
+        "Incomplete (expected 2 coordinates)",
+    ),
+    (
+        "argument.pos3d.incomplete",
+        "Incomplete (expected 3 coordinates)",
+    ),
+    (
+        "argument.pos.mixed",
+        "Cannot mix world & local coordinates (everything must either use ^ or not)",
+    ),
+    ("argument.nbt.expected.key", "Expected key"),
+    ("argument.nbt.expected.value", "Expected value"),
+    ("argument.nbt.trailing", "Unexpected trailing data"),
+    ("argument.nbt.array.mixed", "Can't insert %s into %s"),
+    ("argument.nbt.list.mixed", "Can't insert %s into list of %s"),
+    ("argument.nbt.array.invalid", "Invalid array type '%s'"),
+    ("argument.id.invalid", "Invalid ID"),
+    ("argument.id.unknown", "Unknown ID: %s"),
+    ("argument.block.id.invalid", "Unknown block type '%s'"),
+    (
+        "argument.block.property.unknown",
+        "Block %s does not have property '%s'",
+    ),
+    (
+        "argument.block.property.duplicate",
+        "Property '%s' can only be set once for block %s",
+    ),
+    (
+        "argument.block.property.invalid",
+        "Block %s does not accept '%s' for %s property",
+    ),
+    (
+        "argument.block.property.novalue",
+        "Expected value for property '%s' on block %s",
+    ),
+    (
+        "argument.block.property.unclosed",
+        "Expected closing ] for block state properties",
+    ),
+    ("arguments.block.tag.unknown", "Unknown block tag '%s'"),
+    ("argument.item.id.invalid", "Unknown item '%s'"),
+    ("arguments.item.tag.unknown", "Unknown item tag '%s'"),
+    ("argument.time.invalid_unit", "Invalid unit"),
+    (
+        "argument.time.invalid_tick_count",
+        "The tick count must be non-negative",
+    ),
+    (
+        "argument.time.tick_count_too_low",
+        "The tick count must not be less than %s, found %s",
+    ),
+    ("argument.range.empty", "Expected value or range of values"),
+    ("argument.range.swapped", "Min cannot be bigger than max"),
+    ("argument.component.invalid", "Invalid chat component: %s"),
+    ("argument.literal.incorrect", "Expected literal %s"),
+    (
+        "permissions.requires.player",
+        "A player is required to run this command here",
+    ),
+];
+
+impl Translator for English {
+    fn format(&self, key: &str) -> Option<&str> {
+        ENGLISH
+            .iter()
+            .find(|(english_key, _)| *english_key == key)
+            .map(|(_, format)| *format)
+    }
+}
+
+/// Fills the placeholders of `format` with `args`. Placeholders without an argument are left empty.
+fn fill(format: &str, args: &[String]) -> String {
+    let mut out = String::new();
+    let mut next = 0;
+    let mut rest = format;
+    while let Some(i) = rest.find('%') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+
+        if let Some(after) = rest.strip_prefix('%') {
+            out.push('%');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('s') {
+            out.push_str(args.get(next).map_or("", String::as_str));
+            next += 1;
+            rest = after;
+        } else {
+            let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(0);
+            let index = rest[..digits]
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1));
+            match index {
+                Some(index) if rest[digits..].starts_with("$s") => {
+                    out.push_str(args.get(index).map_or("", String::as_str));
+                    rest = &rest[digits + 2..];
+                }
+                _ => out.push('%'),
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+impl Message {
+    /// Renders the message with the formats of `translator`. Unknown keys are shown as they are.
+    pub fn translate<T: Translator + ?Sized>(&self, translator: &T) -> String {
+        match self {
+            Message::Text(text) => text.clone(),
+            Message::Translatable { key, args } => match translator.format(key) {
+                Some(format) => {
+                    let args: Vec<String> =
+                        args.iter().map(|arg| arg.translate(translator)).collect();
+                    fill(format, &args)
+                }
+                None => key.clone(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::argument::U32Parser;
+    use crate::command::builder::literal;
+    use crate::feedback::Message;
+    use crate::parser::IterParser;
+
+    use super::{fill, English};
+
+    #[test]
+    fn format() {
+        let args = [String::from("a"), String::from("b")];
+        assert_eq!(fill("%s and %s", &args), "a and b");
+        assert_eq!(fill("%2$s before %1$s", &args), "b before a");
+        assert_eq!(fill("100%% %s %s %s", &args), "100% a b ");
+        assert_eq!(fill("%d %0$s", &args), "%d %0$s");
+    }
+
+    #[test]
+    fn translate() {
+        let message = Message::translatable(
+            "argument.integer.low",
+            vec![Message::text("0"), Message::text("-5")],
+        );
+        assert_eq!(
+            message.translate(&English),
+            "Integer must not be less than 0, found -5"
+        );
+        assert_eq!(
+            message.to_string(),
+            "Integer must not be less than 0, found -5"
+        );
+
+        let mut german = HashMap::new();
+        german.insert(
+            String::from("argument.integer.low"),
+            String::from("Ganzzahl darf nicht kleiner als %s sein, %s gefunden"),
+        );
+        assert_eq!(
+            message.translate(&german),
+            "Ganzzahl darf nicht kleiner als 0 sein, -5 gefunden"
+        );
+        assert_eq!(
+            Message::translatable("commands.unknown", vec![]).translate(&English),
+            "commands.unknown"
+        );
+    }
+
+    #[test]
+    fn parser_errors() {
+        fn key<T>(result: anyhow::Result<T>) -> (String, usize) {
+            match Message::from(&result.err().unwrap()) {
+                Message::Translatable { key, args } => (key, args.len()),
+                Message::Text(text) => panic!("untranslatable error: {}", text),
+            }
+        }
+        assert_eq!(
+            key(U32Parser::default().parse((), "x").0),
+            (String::from("parsing.int.expected"), 0)
+        );
+        assert_eq!(
+            key(U32Parser::default().parse((), "-5").0),
+            (String::from("argument.integer.low"), 2)
+        );
+        assert_eq!(
+            key(U32Parser::default().parse((), "99999999999").0),
+            (String::from("argument.integer.big"), 2)
+        );
+        assert_eq!(
+            key(literal("/time").parse((), "/timer").0),
+            (String::from("command.expected.separator"), 0)
+        );
+        assert_eq!(
+            key(literal("/time").parse((), "/tim").0),
+            (String::from("command.unknown.argument"), 0)
+        );
+    }
+}